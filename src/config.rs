@@ -0,0 +1,394 @@
+use anyhow::{Context, Result, anyhow, bail};
+use fs_err as fs;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Politique couleur (`color = auto|always|never`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            other => bail!("expected auto|always|never, got '{other}'"),
+        }
+    }
+}
+
+/// Où vivent les morts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Storage {
+    /// Graveyard nrip + index.json
+    #[default]
+    Graveyard,
+    /// Corbeille FreeDesktop (`~/.local/share/Trash/{files,info}`)
+    Freedesktop,
+}
+
+/// Format de l'index du graveyard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexBackend {
+    /// `index.json`, réécrit en entier à chaque opération
+    #[default]
+    Json,
+    /// `index.log`, opérations ajoutées en fin de fichier (gros graveyards)
+    Log,
+}
+
+/// Confirmation demandée avant de vider tout le graveyard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+pub enum CremateAllConfirm {
+    /// Taper `YES` en toutes lettres
+    #[default]
+    #[serde(rename = "type-YES")]
+    TypeYes,
+    /// Simple `y/N`
+    #[serde(rename = "y/N")]
+    YesNo,
+    /// Aucune confirmation
+    #[serde(rename = "none")]
+    Never,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListConfig {
+    /// Gabarit d'une ligne (voir `template`)
+    pub format: String,
+    /// Format strftime de la date absolue
+    pub time_format: String,
+}
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        Self {
+            format: "{id:7}  {icon} {kind}  {size:>9}  ({deleted_at})  {basename}  {original_path}  ({age})"
+                .into(),
+            time_format: "%Y-%m-%d %H:%M:%S".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FzfConfig {
+    /// Commande d'aperçu; `{trashed_path}` / `{original_path}` sont substitués
+    pub preview: Option<String>,
+    /// Hauteur passée à `fzf --height`
+    pub height: String,
+}
+
+impl Default for FzfConfig {
+    fn default() -> Self {
+        Self {
+            preview: None,
+            height: "40%".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfirmConfig {
+    /// Demander `y/N` avant de restaurer un seul élément
+    pub resurrect: bool,
+    pub cremate_all: CremateAllConfirm,
+}
+
+impl Default for ConfirmConfig {
+    fn default() -> Self {
+        Self {
+            resurrect: true,
+            cremate_all: CremateAllConfirm::TypeYes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PruneConfig {
+    /// Rétention par défaut de `--prune` (durée humantime, ex. "30d")
+    pub retention: Option<String>,
+    /// Élaguer automatiquement après chaque `bury`
+    pub auto: bool,
+}
+
+impl PruneConfig {
+    pub fn retention(&self) -> Option<Duration> {
+        self.retention
+            .as_deref()
+            .and_then(|r| humantime::parse_duration(r).ok())
+    }
+}
+
+/// Que faire quand un `bury` dépasserait le quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaPolicy {
+    /// Crémer les plus anciennes entrées d'abord
+    #[default]
+    Evict,
+    /// Refuser l'enterrement
+    Refuse,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuotaConfig {
+    /// Taille max du graveyard principal (ex. "10GiB"); ceux des autres montages ne comptent pas
+    pub max_size: Option<String>,
+    /// Nombre max d'entrées
+    pub max_items: Option<usize>,
+    pub policy: QuotaPolicy,
+}
+
+impl QuotaConfig {
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_size
+            .as_deref()
+            .and_then(|s| crate::size::parse_size(s).ok())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LockConfig {
+    /// Attente max du verrou de l'index (durée humantime; "0s" = échouer aussitôt)
+    pub timeout: String,
+}
+
+impl Default for LockConfig {
+    fn default() -> Self {
+        Self {
+            timeout: "60s".into(),
+        }
+    }
+}
+
+impl LockConfig {
+    pub fn timeout(&self) -> Duration {
+        humantime::parse_duration(&self.timeout).unwrap_or(Duration::from_secs(60))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexConfig {
+    pub backend: IndexBackend,
+}
+
+/// `~/.config/nrip/config.toml`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub graveyard_dir: Option<PathBuf>,
+    pub storage: Storage,
+    /// Enterrer dans `<mount>/.nrip-graveyard-$UID` quand la source est sur un autre FS
+    pub per_mount_graveyards: bool,
+    pub color: ColorMode,
+    pub list: ListConfig,
+    pub fzf: FzfConfig,
+    pub confirm: ConfirmConfig,
+    pub prune: PruneConfig,
+    pub quota: QuotaConfig,
+    pub index: IndexConfig,
+    pub lock: LockConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            graveyard_dir: None,
+            storage: Storage::default(),
+            per_mount_graveyards: true,
+            color: ColorMode::default(),
+            list: ListConfig::default(),
+            fzf: FzfConfig::default(),
+            confirm: ConfirmConfig::default(),
+            prune: PruneConfig::default(),
+            quota: QuotaConfig::default(),
+            index: IndexConfig::default(),
+            lock: LockConfig::default(),
+        }
+    }
+}
+
+/// Surcharges en ligne de commande (prioritaires sur env et fichier)
+#[derive(Debug, Default)]
+pub struct Overrides {
+    pub config: Option<PathBuf>,
+    pub graveyard_dir: Option<PathBuf>,
+    pub color: Option<ColorMode>,
+    /// `--no-wait`: verrou de l'index pris ou échec immédiat
+    pub no_wait: bool,
+    /// `--format` / `--time-format` de `nrip -l`
+    pub list_format: Option<String>,
+    pub time_format: Option<String>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Chemin du fichier de config: `$NRIP_CONFIG`, sinon `${XDG_CONFIG_HOME:-~/.config}/nrip/config.toml`
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(p) = std::env::var_os("NRIP_CONFIG") {
+        return Some(PathBuf::from(p));
+    }
+    dirs::config_dir().map(|d| d.join("nrip").join("config.toml"))
+}
+
+fn expand_tilde(p: &Path) -> PathBuf {
+    match p.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().map(|h| h.join(rest)).unwrap_or_else(|| p.to_path_buf()),
+        Err(_) => p.to_path_buf(),
+    }
+}
+
+impl Config {
+    pub fn from_file(path: &Path) -> Result<Config> {
+        let text = fs::read_to_string(path)?;
+        let mut cfg: Config =
+            toml::from_str(&text).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        if let Some(d) = cfg.graveyard_dir.as_mut() {
+            *d = expand_tilde(d);
+        }
+        cfg.validate()
+            .with_context(|| format!("invalid config {}", path.display()))?;
+        Ok(cfg)
+    }
+
+    /// Vérifie les valeurs; l'erreur nomme la clé fautive
+    pub fn validate(&self) -> Result<()> {
+        if let Some(d) = &self.graveyard_dir
+            && !d.is_absolute()
+        {
+            bail!(
+                "`graveyard_dir`: must be an absolute path, got '{}'",
+                d.display()
+            );
+        }
+        crate::template::Template::parse(&self.list.format)
+            .map_err(|e| anyhow!("`list.format`: {e}"))?;
+        use chrono::format::{Item, StrftimeItems};
+        if self.list.time_format.is_empty()
+            || StrftimeItems::new(&self.list.time_format).any(|i| matches!(i, Item::Error))
+        {
+            bail!(
+                "`list.time_format`: invalid strftime format '{}'",
+                self.list.time_format
+            );
+        }
+        let h = self.fzf.height.strip_prefix('~').unwrap_or(&self.fzf.height);
+        let h = h.strip_suffix('%').unwrap_or(h);
+        if h.is_empty() || !h.chars().all(|c| c.is_ascii_digit()) {
+            bail!(
+                "`fzf.height`: expected N or N% (e.g. \"40%\"), got '{}'",
+                self.fzf.height
+            );
+        }
+        if let Some(p) = &self.fzf.preview
+            && p.trim().is_empty()
+        {
+            bail!("`fzf.preview`: empty command");
+        }
+        if let Some(r) = &self.prune.retention {
+            humantime::parse_duration(r)
+                .map_err(|e| anyhow!("`prune.retention`: invalid duration '{r}': {e}"))?;
+        }
+        if let Some(m) = &self.quota.max_size {
+            crate::size::parse_size(m).map_err(|e| anyhow!("`quota.max_size`: {e}"))?;
+        }
+        if self.quota.max_items == Some(0) {
+            bail!("`quota.max_items`: must be at least 1");
+        }
+        humantime::parse_duration(&self.lock.timeout).map_err(|e| {
+            anyhow!("`lock.timeout`: invalid duration '{}': {e}", self.lock.timeout)
+        })?;
+        if self.prune.auto && self.prune.retention.is_none() {
+            bail!("`prune.auto`: requires `prune.retention`");
+        }
+        Ok(())
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        if let Some(d) = std::env::var_os("NRIP_GRAVEYARD_DIR") {
+            self.graveyard_dir = Some(PathBuf::from(d));
+        }
+        if let Ok(c) = std::env::var("NRIP_COLOR") {
+            self.color = c.parse().context("NRIP_COLOR")?;
+        }
+        if let Ok(t) = std::env::var("NRIP_LOCK_TIMEOUT") {
+            self.lock.timeout = t;
+        }
+        Ok(())
+    }
+
+    fn apply_overrides(&mut self, o: &Overrides) {
+        if let Some(d) = &o.graveyard_dir {
+            self.graveyard_dir = Some(d.clone());
+        }
+        if let Some(c) = o.color {
+            self.color = c;
+        }
+        if o.no_wait {
+            self.lock.timeout = "0s".into();
+        }
+        if let Some(f) = &o.list_format {
+            self.list.format = f.clone();
+        }
+        if let Some(f) = &o.time_format {
+            self.list.time_format = f.clone();
+        }
+    }
+}
+
+/// Fichier → variables d'env → CLI, puis validation finale
+pub fn load(o: &Overrides) -> Result<Config> {
+    let mut cfg = match o.config.clone().or_else(config_path) {
+        Some(p) if p.exists() => Config::from_file(&p)?,
+        Some(p) if o.config.is_some() => bail!("config file not found: {}", p.display()),
+        _ => Config::default(),
+    };
+    cfg.apply_env()?;
+    cfg.apply_overrides(o);
+    // env/CLI: chemins relatifs au cwd acceptés
+    if let Some(d) = &cfg.graveyard_dir {
+        let abs = std::path::absolute(expand_tilde(d))
+            .with_context(|| format!("absolutize {}", d.display()))?;
+        cfg.graveyard_dir = Some(abs);
+    }
+    cfg.validate()?;
+    Ok(cfg)
+}
+
+/// Installe la config globale (à appeler une fois depuis main)
+pub fn install(cfg: Config) {
+    let _ = CONFIG.set(cfg);
+}
+
+/// Config courante. `main` l'installe après un `load` qui échoue bruyamment; sans `install`
+/// (usage en bibliothèque), fichier + env sont chargés au premier accès et une config
+/// invalide est signalée une fois avant de retomber sur les défauts
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        load(&Overrides::default()).unwrap_or_else(|e| {
+            eprintln!("warn: ignoring the config ({e:#}); using defaults");
+            Config::default()
+        })
+    })
+}
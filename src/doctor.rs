@@ -0,0 +1,280 @@
+//! `--doctor`: confronte l'index aux graveyards sur disque, `--fix` répare.
+
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::{Value, json};
+use fs_err as fs;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::config::Storage;
+use crate::fs_safemove::parse_unique_name;
+use crate::index::{Entry, Index, Kind};
+use crate::journal::{self, Record};
+use crate::output::{self, say};
+
+/// Fichiers de service d'un graveyard, jamais signalés
+const META: [&str; 3] = [".journal", ".journal.1", ".index.lock"];
+
+#[derive(Debug)]
+enum Issue {
+    /// Opération du journal jamais close par `COMMIT` (rejouée par `--recover` ou `--fix`)
+    Interrupted { what: &'static str, path: PathBuf },
+    /// Présent dans un graveyard, absent de l'index (origine issue du journal si connue)
+    Orphan {
+        path: PathBuf,
+        original: Option<PathBuf>,
+    },
+    /// Entrée dont le `trashed_path` n'existe plus
+    Dangling { original: PathBuf, trashed: PathBuf },
+    /// Plusieurs entrées pour le même `trashed_path`
+    Duplicate { trashed: PathBuf, count: usize },
+    /// Reste d'une copie inter-device interrompue
+    StaleTemp { path: PathBuf },
+}
+
+impl Issue {
+    /// Enregistrement `--json`: `{"action": "issue", "issue": ..., "path": ..., "fixed": ...}`
+    fn record(&self, fixed: bool) -> Value {
+        let mut v = match self {
+            Issue::Interrupted { what, path } => {
+                json!({ "issue": "interrupted", "operation": what, "path": path })
+            }
+            Issue::Orphan { path, original } => {
+                json!({ "issue": "orphan", "path": path, "original_path": original })
+            }
+            Issue::Dangling { original, trashed } => {
+                json!({ "issue": "dangling", "path": trashed, "original_path": original })
+            }
+            Issue::Duplicate { trashed, count } => {
+                json!({ "issue": "duplicate", "path": trashed, "count": count })
+            }
+            Issue::StaleTemp { path } => json!({ "issue": "stale-temp", "path": path }),
+        };
+        v["action"] = "issue".into();
+        v["fixed"] = fixed.into();
+        v
+    }
+}
+
+/// Sans `--fix`: simple lecture (index, journal, graveyards), rien n'est écrit
+pub fn doctor(fix: bool) -> Result<()> {
+    if crate::config::get().storage == Storage::Freedesktop {
+        anyhow::bail!("--doctor checks nrip graveyards; it does not support storage = \"freedesktop\"");
+    }
+    let (issues, fixed, odd_names) = if fix {
+        // Le journal est rejoué par la transaction elle-même
+        crate::index::with_index_mut(|idx| {
+            let issues = examine(idx)?;
+            let fixed = repair(idx, &issues)?;
+            Ok((issues, fixed, odd_names(idx)))
+        })?
+    } else {
+        let idx = crate::index::load_index()?;
+        let mut issues = interrupted()?;
+        issues.extend(examine(&idx)?);
+        let none = vec![false; issues.len()];
+        (issues, none, odd_names(&idx))
+    };
+
+    for p in &odd_names {
+        say!("warn        unparseable graveyard name: {}", p.display());
+        output::emit(json!({ "action": "warning", "warning": "unparseable-name", "path": p }));
+    }
+    for (i, &ok) in issues.iter().zip(&fixed) {
+        output::emit(i.record(ok));
+        match i {
+            Issue::Interrupted { what, path } => {
+                say!("interrupted {what} of {}  (run `nrip --recover`)", path.display())
+            }
+            Issue::Orphan {
+                path,
+                original: Some(o),
+            } => say!("orphan      {}  (was {})", path.display(), o.display()),
+            Issue::Orphan {
+                path,
+                original: None,
+            } => say!("orphan      {}  (original path unknown)", path.display()),
+            Issue::Dangling { original, trashed } => say!(
+                "dangling    {}  ({} is gone)",
+                original.display(),
+                trashed.display()
+            ),
+            Issue::Duplicate { trashed, count } => {
+                say!("duplicate   {}  ({count} entries)", trashed.display())
+            }
+            Issue::StaleTemp { path } => say!("stale-temp  {}", path.display()),
+        }
+    }
+
+    if issues.is_empty() {
+        say!("Graveyard is healthy.");
+        return Ok(());
+    }
+    if !fix {
+        anyhow::bail!(
+            "{} issue(s) found; run `nrip --doctor --fix` to repair",
+            issues.len()
+        );
+    }
+    let fixed = fixed.iter().filter(|&&ok| ok).count();
+    say!("Fixed {fixed} of {} issue(s).", issues.len());
+    if fixed < issues.len() {
+        anyhow::bail!("{} issue(s) need manual attention", issues.len() - fixed);
+    }
+    Ok(())
+}
+
+/// Opérations ouvertes du journal, lu sans être rejoué
+fn interrupted() -> Result<Vec<Issue>> {
+    Ok(journal::open_records()?
+        .into_iter()
+        .filter_map(|r| match r {
+            Record::Bury { original, .. } => Some(Issue::Interrupted {
+                what: "burial",
+                path: original,
+            }),
+            Record::Restore { original, .. } => Some(Issue::Interrupted {
+                what: "restore",
+                path: original,
+            }),
+            Record::Evict { trashed } => Some(Issue::Interrupted {
+                what: "eviction",
+                path: trashed,
+            }),
+            Record::Commit => None,
+        })
+        .collect())
+}
+
+/// Graveyards à inspecter: principal, ceux des entrées et ceux cités par le journal
+fn graveyards(idx: &Index, history: &[Record]) -> Result<Vec<PathBuf>> {
+    let mut out = vec![crate::paths::graveyard_dir()?];
+    out.extend(idx.items.iter().filter_map(|e| e.graveyard.clone()));
+    out.extend(history.iter().filter_map(|r| match r {
+        Record::Bury { trashed, .. } => journal::holder_of(trashed),
+        _ => None,
+    }));
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+fn examine(idx: &Index) -> Result<Vec<Issue>> {
+    let history = journal::read_all()?;
+    // Dernier chemin d'origine connu pour chaque chemin du graveyard
+    let mut origin: HashMap<&Path, &Path> = HashMap::new();
+    for r in &history {
+        if let Record::Bury { original, trashed } = r {
+            origin.insert(trashed, original);
+        }
+    }
+
+    let mut issues = Vec::new();
+    let mut counts: HashMap<&Path, usize> = HashMap::new();
+    for e in &idx.items {
+        *counts.entry(&e.trashed_path).or_default() += 1;
+    }
+    let mut dup_seen = HashSet::new();
+    for e in &idx.items {
+        let n = counts[e.trashed_path.as_path()];
+        if n > 1 && dup_seen.insert(&e.trashed_path) {
+            issues.push(Issue::Duplicate {
+                trashed: e.trashed_path.clone(),
+                count: n,
+            });
+        }
+        if std::fs::symlink_metadata(&e.trashed_path).is_err() {
+            issues.push(Issue::Dangling {
+                original: e.original_path.clone(),
+                trashed: e.trashed_path.clone(),
+            });
+        }
+    }
+
+    for gy in graveyards(idx, &history)? {
+        let Ok(rd) = fs::read_dir(&gy) else { continue };
+        let mut names: Vec<PathBuf> = rd.filter_map(|r| r.ok()).map(|d| d.path()).collect();
+        names.sort();
+        for p in names {
+            let name = p.file_name().unwrap_or_default().to_string_lossy();
+            if META.contains(&name.as_ref()) {
+                continue;
+            }
+            if name.ends_with(".copying") {
+                issues.push(Issue::StaleTemp { path: p });
+            } else if !counts.contains_key(p.as_path()) {
+                let original = origin.get(p.as_path()).map(|o| o.to_path_buf());
+                issues.push(Issue::Orphan { path: p, original });
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Entrées dont le nom ne suit pas `YYYYMMDDTHHMMSS__RANDOM__basename`
+fn odd_names(idx: &Index) -> Vec<PathBuf> {
+    idx.items
+        .iter()
+        .map(|e| &e.trashed_path)
+        .filter(|p| {
+            let name = p.file_name().unwrap_or_default().to_string_lossy();
+            parse_unique_name(&name).is_none()
+        })
+        .cloned()
+        .collect()
+}
+
+/// Applique les réparations sûres; pour chaque problème, s'il est réglé
+fn repair(idx: &mut Index, issues: &[Issue]) -> Result<Vec<bool>> {
+    let mut fixed = Vec::with_capacity(issues.len());
+    for i in issues {
+        let ok = match i {
+            Issue::Orphan {
+                path,
+                original: Some(original),
+            } => {
+                let md = std::fs::symlink_metadata(path)?;
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let deleted_at = parse_unique_name(&name)
+                    .map(|(ts, _)| ts)
+                    .unwrap_or_else(|| Utc::now().timestamp());
+                let usage = crate::size::measure(path);
+                idx.items.push(Entry {
+                    original_path: original.clone(),
+                    trashed_path: path.clone(),
+                    deleted_at,
+                    kind: Kind::from_file_type(md.file_type()),
+                    graveyard: journal::holder_of(path),
+                    size: Some(usage.apparent),
+                    disk_usage: Some(usage.disk),
+                    extra: Default::default(),
+                });
+                journal::append(&format!("RECOVERED\tADOPTED\t{}", path.display()))?;
+                true
+            }
+            Issue::Orphan { original: None, .. } | Issue::Interrupted { .. } => false,
+            Issue::Dangling { trashed, .. } => {
+                idx.items.retain(|e| e.trashed_path != *trashed);
+                true
+            }
+            Issue::Duplicate { trashed, .. } => {
+                // Garde la première entrée (la plus ancienne)
+                let mut kept = false;
+                idx.items.retain(|e| {
+                    if e.trashed_path != *trashed {
+                        return true;
+                    }
+                    !std::mem::replace(&mut kept, true)
+                });
+                true
+            }
+            Issue::StaleTemp { path } => {
+                crate::fs_safemove::remove_recursively(path)?;
+                true
+            }
+        };
+        fixed.push(ok);
+    }
+    Ok(fixed)
+}
@@ -0,0 +1,154 @@
+//! Sélection d'entrées: filtres, tri et limite communs (`--list` aujourd'hui,
+//! à terme la sélection de `--cremate` / `--resurrect`).
+//! Ordre d'application: filtres → tri → `--reverse` → `--limit`.
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::index::{Entry, Kind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Date d'enterrement, du plus ancien au plus récent
+    Date,
+    /// Nom d'origine, sans tenir compte de la casse
+    Name,
+    /// Taille apparente, du plus petit au plus gros
+    Size,
+    /// Chemin d'origine complet
+    Path,
+    /// F, D, L puis ?
+    Kind,
+}
+
+impl FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "date" => Ok(SortKey::Date),
+            "name" => Ok(SortKey::Name),
+            "size" => Ok(SortKey::Size),
+            "path" => Ok(SortKey::Path),
+            "kind" => Ok(SortKey::Kind),
+            other => bail!("expected date|name|size|path|kind, got '{other}'"),
+        }
+    }
+}
+
+/// Motif de `--match`: glob par défaut, expression régulière avec le préfixe `re:`.
+/// Sans `/`, le motif porte sur le nom; avec, sur le chemin d'origine complet.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Glob {
+        matcher: globset::GlobMatcher,
+        whole_path: bool,
+    },
+    Regex(regex::Regex),
+}
+
+impl FromStr for Pattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(re) = s.strip_prefix("re:") {
+            let re = regex::Regex::new(re).map_err(|e| anyhow!("invalid regex: {e}"))?;
+            return Ok(Pattern::Regex(re));
+        }
+        let glob = globset::GlobBuilder::new(s)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| anyhow!("invalid glob: {e}"))?;
+        Ok(Pattern::Glob {
+            matcher: glob.compile_matcher(),
+            whole_path: s.contains('/'),
+        })
+    }
+}
+
+impl Pattern {
+    fn is_match(&self, e: &Entry) -> bool {
+        match self {
+            Pattern::Glob {
+                matcher,
+                whole_path: true,
+            } => matcher.is_match(&e.original_path),
+            Pattern::Glob { matcher, .. } => {
+                matcher.is_match(e.original_path.file_name().unwrap_or_default())
+            }
+            // Regex: cherchée n'importe où dans le chemin d'origine (ancrer avec ^/$)
+            Pattern::Regex(re) => re.is_match(&e.original_path.to_string_lossy()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Enterrées il y a moins de …
+    pub since: Option<Duration>,
+    /// Enterrées il y a plus de …
+    pub before: Option<Duration>,
+    /// Types acceptés (vide = tous)
+    pub kinds: Vec<Kind>,
+    /// Chemin d'origine sous ce dossier (absolu)
+    pub under: Option<PathBuf>,
+    pub pattern: Option<Pattern>,
+    pub sort: Option<SortKey>,
+    pub reverse: bool,
+    pub limit: Option<usize>,
+}
+
+/// `--under`: relatif au dossier courant, `~/` accepté
+pub fn parse_under(s: &str) -> Result<PathBuf> {
+    let p = Path::new(s);
+    let p = match p.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().context("no home directory")?.join(rest),
+        Err(_) => p.to_path_buf(),
+    };
+    Ok(std::path::absolute(&p)?)
+}
+
+impl Filter {
+    fn keeps(&self, e: &Entry, now: i64) -> bool {
+        let age = now.saturating_sub(e.deleted_at).max(0) as u64;
+        self.since.is_none_or(|d| age <= d.as_secs())
+            && self.before.is_none_or(|d| age > d.as_secs())
+            && (self.kinds.is_empty() || self.kinds.contains(&e.kind))
+            && self.under.as_deref().is_none_or(|d| e.original_path.starts_with(d))
+            && self.pattern.as_ref().is_none_or(|p| p.is_match(e))
+    }
+
+    /// Sélection finale, dans l'ordre d'affichage
+    pub fn apply(&self, entries: Vec<Entry>) -> Vec<Entry> {
+        let now = Utc::now().timestamp();
+        let mut out: Vec<Entry> = entries.into_iter().filter(|e| self.keeps(e, now)).collect();
+        // Tris stables: à clé égale, l'ordre de l'index est conservé
+        match self.sort {
+            None => {}
+            Some(SortKey::Date) => out.sort_by_key(|e| e.deleted_at),
+            Some(SortKey::Name) => {
+                out.sort_by_cached_key(|e| crate::index::basename_of_original(e).to_lowercase())
+            }
+            Some(SortKey::Size) => {
+                out.sort_by_cached_key(|e| crate::size::entry_usage(e).apparent)
+            }
+            Some(SortKey::Path) => out.sort_by(|a, b| a.original_path.cmp(&b.original_path)),
+            Some(SortKey::Kind) => out.sort_by_key(|e| match e.kind {
+                Kind::File => 0,
+                Kind::Dir => 1,
+                Kind::Symlink => 2,
+                Kind::Other => 3,
+            }),
+        }
+        if self.reverse {
+            out.reverse();
+        }
+        if let Some(n) = self.limit {
+            out.truncate(n);
+        }
+        out
+    }
+}
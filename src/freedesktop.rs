@@ -0,0 +1,377 @@
+//! Backend « corbeille FreeDesktop » (Trash spec 1.0):
+//! `$XDG_DATA_HOME/Trash/{files,info}` + `$topdir/.Trash-$uid` sur les autres montages.
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use fs_err as fs;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::fs_safemove::{safe_move, safe_move_deferred, unique_name};
+use crate::index::{Entry, Kind};
+
+const DATE_FMT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// `$XDG_DATA_HOME/Trash`
+pub fn home_trash() -> Result<PathBuf> {
+    let base = dirs::data_dir().context("XDG data dir not found")?;
+    Ok(base.join("Trash"))
+}
+
+#[cfg(unix)]
+fn uid() -> u32 {
+    rustix::process::getuid().as_raw()
+}
+
+/// `$topdir/.Trash` partagé: vrai dossier (pas un symlink) avec le sticky bit
+#[cfg(unix)]
+fn is_valid_shared_trash(dot_trash: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match std::fs::symlink_metadata(dot_trash) {
+        Ok(md) => md.is_dir() && md.permissions().mode() & 0o1000 != 0,
+        Err(_) => false,
+    }
+}
+
+/// Corbeilles candidates d'un point de montage, par ordre de préférence
+#[cfg(unix)]
+fn topdir_trashes(top: &Path) -> Vec<PathBuf> {
+    let mut out = Vec::with_capacity(2);
+    let shared = top.join(".Trash");
+    if is_valid_shared_trash(&shared) {
+        out.push(shared.join(uid().to_string()));
+    }
+    out.push(top.join(format!(".Trash-{}", uid())));
+    out
+}
+
+/// Points de montage (Linux: /proc/self/mounts, échappements octaux `\040`)
+fn mount_points() -> Vec<PathBuf> {
+    let Ok(text) = std::fs::read_to_string("/proc/self/mounts") else {
+        return vec![];
+    };
+    let mut out: Vec<PathBuf> = text
+        .lines()
+        .filter_map(|l| l.split(' ').nth(1))
+        .map(|mp| PathBuf::from(unescape_mount(mp)))
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+fn unescape_mount(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'\\'
+            && let Some(v) = s.get(i + 1..i + 4).and_then(|o| u8::from_str_radix(o, 8).ok())
+        {
+            out.push(v);
+            i += 4;
+            continue;
+        }
+        out.push(b[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Encodage `Path=` (RFC 2396): tout sauf les non-réservés et `/`
+pub fn percent_encode(p: &Path) -> String {
+    let mut out = String::new();
+    for &b in p.as_os_str().as_encoded_bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.!~*'()/".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+pub fn percent_decode(s: &str) -> PathBuf {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'%'
+            && let Some(v) = s.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok())
+        {
+            out.push(v);
+            i += 3;
+            continue;
+        }
+        out.push(b[i]);
+        i += 1;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        PathBuf::from(std::ffi::OsString::from_vec(out))
+    }
+    #[cfg(windows)]
+    {
+        PathBuf::from(String::from_utf8_lossy(&out).into_owned())
+    }
+}
+
+/// Contenu d'un fichier `.trashinfo`
+fn trashinfo(path_field: &str, deleted_at: i64) -> String {
+    let when = Local
+        .timestamp_opt(deleted_at, 0)
+        .single()
+        .unwrap_or_else(Local::now);
+    format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        path_field,
+        when.format(DATE_FMT)
+    )
+}
+
+/// (Path décodé, DeletionDate en secondes Unix) du groupe `[Trash Info]`
+fn parse_trashinfo(text: &str) -> Option<(PathBuf, i64)> {
+    let mut in_group = false;
+    let (mut path, mut date) = (None, None);
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_group = line == "[Trash Info]";
+            continue;
+        }
+        if !in_group {
+            continue;
+        }
+        if let Some(v) = line.strip_prefix("Path=") {
+            path.get_or_insert_with(|| percent_decode(v));
+        } else if let Some(v) = line.strip_prefix("DeletionDate=") {
+            date = NaiveDateTime::parse_from_str(v, DATE_FMT)
+                .ok()
+                .and_then(|d| Local.from_local_datetime(&d).earliest())
+                .map(|d| d.timestamp());
+        }
+    }
+    // DeletionDate absente/illisible: on garde l'entrée, datée à 0
+    Some((path?, date.unwrap_or(0)))
+}
+
+/// Corbeilles existantes: (dossier Trash, topdir si corbeille de montage)
+pub fn trash_dirs() -> Result<Vec<(PathBuf, Option<PathBuf>)>> {
+    let home = home_trash()?;
+    let mut out = vec![(home.clone(), None)];
+    #[cfg(unix)]
+    for top in mount_points() {
+        for t in topdir_trashes(&top) {
+            if t != home && t.join("info").is_dir() && !out.iter().any(|(d, _)| *d == t) {
+                out.push((t, Some(top.clone())));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Toutes les entrées de toutes les corbeilles, triées par date de suppression
+pub fn scan() -> Result<Vec<Entry>> {
+    let mut items = Vec::new();
+    for (dir, top) in trash_dirs()? {
+        let Ok(rd) = fs::read_dir(dir.join("info")) else {
+            continue;
+        };
+        for ent in rd.filter_map(|r| r.ok()) {
+            let info = ent.path();
+            if info.extension() != Some(OsStr::new("trashinfo")) {
+                continue;
+            }
+            let Some(name) = info.file_stem() else { continue };
+            let trashed = dir.join("files").join(name);
+            // info sans fichier = orphelin (spec): ignoré
+            let Ok(md) = std::fs::symlink_metadata(&trashed) else {
+                continue;
+            };
+            let Ok(text) = std::fs::read_to_string(&info) else {
+                continue;
+            };
+            let Some((path, deleted_at)) = parse_trashinfo(&text) else {
+                continue;
+            };
+            let original_path = match (&top, path.is_absolute()) {
+                (_, true) => path,
+                (Some(t), false) => t.join(path),
+                (None, false) => continue, // chemin relatif interdit dans la corbeille home
+            };
+            items.push(Entry {
+                original_path,
+                trashed_path: trashed,
+                deleted_at,
+                kind: Kind::from_file_type(md.file_type()),
+                graveyard: top.as_ref().map(|_| dir.clone()),
+                size: None,
+                disk_usage: None,
+                extra: Default::default(),
+            });
+        }
+    }
+    items.sort_by_key(|e| e.deleted_at);
+    Ok(items)
+}
+
+/// `<trash>/info/<name>.trashinfo` pour `<trash>/files/<name>`
+pub fn info_path_for(trashed: &Path) -> Option<PathBuf> {
+    let name = trashed.file_name()?;
+    let trash = trashed.parent()?.parent()?;
+    let mut info = name.to_os_string();
+    info.push(".trashinfo");
+    Some(trash.join("info").join(info))
+}
+
+/// topdir d'une corbeille de montage (`$top/.Trash-$uid` ou `$top/.Trash/$uid`)
+fn topdir_of(trash: &Path) -> Option<PathBuf> {
+    let parent = trash.parent()?;
+    let name = trash.file_name()?.to_str()?;
+    if name.starts_with(".Trash-") {
+        Some(parent.to_path_buf())
+    } else if parent.file_name() == Some(OsStr::new(".Trash")) {
+        parent.parent().map(Path::to_path_buf)
+    } else {
+        None
+    }
+}
+
+/// Valeur `Path=`: relative au topdir pour une corbeille de montage
+fn path_field(original: &Path, top: Option<&Path>) -> String {
+    match top.and_then(|t| original.strip_prefix(t).ok()) {
+        Some(rel) => percent_encode(rel),
+        None => percent_encode(original),
+    }
+}
+
+/// Écrit un `.trashinfo` de façon atomique (O_EXCL); `false` si le nom est déjà pris
+fn write_info(info: &Path, content: &str) -> Result<bool> {
+    let mut f = match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(info)
+    {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    f.write_all(content.as_bytes())?;
+    f.sync_all()?;
+    Ok(true)
+}
+
+/// Corbeille cible pour `original` (même logique de montage que les graveyards)
+fn target_trash(original: &Path) -> Result<(PathBuf, Option<PathBuf>)> {
+    let home = home_trash()?;
+    #[cfg(unix)]
+    if crate::config::get().per_mount_graveyards
+        && crate::mounts::on_other_device(original, &home)?
+        && let Ok(top) =
+            crate::mounts::mount_root(original.parent().unwrap_or(Path::new("/")))
+    {
+        for t in topdir_trashes(&top) {
+            if let Ok(t) = crate::mounts::ensure_private_dir(&t) {
+                return Ok((t, Some(top)));
+            }
+        }
+    }
+    Ok((home, None))
+}
+
+/// Nom réservé dans une corbeille: le `.trashinfo` existe déjà, `files/<name>` pas encore
+pub struct Reservation {
+    pub dst: PathBuf,
+    info: PathBuf,
+    /// Corbeille de montage éventuelle (None = corbeille home)
+    pub holder: Option<PathBuf>,
+}
+
+/// Réserve un nom pour `original` en écrivant d'abord son `.trashinfo` (O_EXCL)
+pub fn reserve(original: &Path) -> Result<Reservation> {
+    let (trash, top) = target_trash(original)?;
+    fs::create_dir_all(trash.join("files"))?;
+    fs::create_dir_all(trash.join("info"))?;
+
+    let base = original.file_name().unwrap_or_default();
+    let content = trashinfo(
+        &path_field(original, top.as_deref()),
+        chrono::Utc::now().timestamp(),
+    );
+    for _ in 0..16 {
+        let name = unique_name(base);
+        let info = trash.join("info").join(format!("{name}.trashinfo"));
+        if write_info(&info, &content)? {
+            return Ok(Reservation {
+                dst: trash.join("files").join(name),
+                info,
+                holder: top.map(|_| trash),
+            });
+        }
+    }
+    anyhow::bail!("cannot reserve a .trashinfo name")
+}
+
+impl Reservation {
+    /// Déplace `src` dans `files/`; libère la réservation si rien n'y a été créé
+    pub fn fill(self, src: &Path) -> Result<(PathBuf, Option<PathBuf>)> {
+        self.fill_with(src, safe_move)?;
+        Ok((self.dst, self.holder))
+    }
+
+    /// `fill` pour un lot: fsync de `files/` laissé à l'appelant
+    pub fn fill_deferred(&self, src: &Path) -> Result<()> {
+        self.fill_with(src, safe_move_deferred)
+    }
+
+    /// Abandonne la réservation (`.trashinfo` supprimé, absent = ok)
+    pub fn release(self) {
+        let _ = fs::remove_file(&self.info);
+    }
+
+    fn fill_with(&self, src: &Path, mv: fn(&Path, &Path) -> Result<()>) -> Result<()> {
+        if let Err(e) = mv(src, &self.dst) {
+            // Cible déjà créée (copie faite, source non retirée): le `.trashinfo` la garde visible
+            if std::fs::symlink_metadata(&self.dst).is_err() {
+                let _ = fs::remove_file(&self.info);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+/// Supprime le `.trashinfo` d'un élément de corbeille (absent = ok)
+pub fn remove_info(trashed: &Path) -> Result<()> {
+    let Some(info) = info_path_for(trashed) else {
+        return Ok(());
+    };
+    match fs::remove_file(&info) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Répercute une transaction sur les `.trashinfo`: entrées retirées → info supprimé,
+/// entrées ajoutées sans info → info écrit
+pub fn commit(before: &[Entry], after: &[Entry]) -> Result<()> {
+    let kept: HashSet<&Path> = after.iter().map(|e| e.trashed_path.as_path()).collect();
+    let prev: HashSet<&Path> = before.iter().map(|e| e.trashed_path.as_path()).collect();
+    for e in before.iter().filter(|e| !kept.contains(e.trashed_path.as_path())) {
+        remove_info(&e.trashed_path)?;
+    }
+    for e in after.iter().filter(|e| !prev.contains(e.trashed_path.as_path())) {
+        let Some(info) = info_path_for(&e.trashed_path) else {
+            continue;
+        };
+        let top = e.graveyard.as_deref().and_then(topdir_of);
+        write_info(
+            &info,
+            &trashinfo(&path_field(&e.original_path, top.as_deref()), e.deleted_at),
+        )?;
+    }
+    Ok(())
+}
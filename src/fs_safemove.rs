@@ -2,14 +2,23 @@ use anyhow::{Context, Result};
 use base64::Engine;
 use fs_err as fs;
 use rand::{RngCore, rng}; // rand 0.9
-use std::ffi::OsString;
+use std::collections::HashMap;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
+use crate::progress::{InterruptGuard, Progress, check_interrupt};
+
 #[cfg(unix)]
 use rustix::fs::{Mode, OFlags, open};
 #[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+#[cfg(unix)]
 use std::os::unix::fs::symlink;
 
+/// Taille des morceaux copiés entre deux points de progression/interruption
+#[cfg(target_os = "linux")]
+const CHUNK: u64 = 8 << 20;
+
 fn rand_suffix() -> String {
     let mut b = [0u8; 6];
     rng().fill_bytes(&mut b); // rand 0.9
@@ -17,7 +26,7 @@ fn rand_suffix() -> String {
 }
 
 /// Fsync d’un répertoire (durabilité des entrées)
-fn fsync_dir(dir: &Path) -> Result<()> {
+pub fn fsync_dir(dir: &Path) -> Result<()> {
     #[cfg(unix)]
     {
         let f = open(dir, OFlags::RDONLY | OFlags::DIRECTORY, Mode::empty())
@@ -33,7 +42,11 @@ fn fsync_dir(dir: &Path) -> Result<()> {
 }
 
 /// rendu visible pour graveyard.rs
+/// (fs_err enveloppe l'erreur: raw_os_error() est perdu, mais pas le kind)
 pub(crate) fn is_exdev(err: &std::io::Error) -> bool {
+    if err.kind() == std::io::ErrorKind::CrossesDevices {
+        return true;
+    }
     match err.raw_os_error() {
         #[cfg(unix)]
         Some(n) => n == libc::EXDEV,
@@ -43,30 +56,322 @@ pub(crate) fn is_exdev(err: &std::io::Error) -> bool {
     }
 }
 
-fn copy_file_fsync(src: &Path, dst: &Path) -> Result<()> {
+/// `copy_file_range` de `len` octets à partir de `off` (copie dans le noyau, sans
+/// passer par des buffers userspace). `false` si le noyau/FS refuse d'emblée.
+#[cfg(target_os = "linux")]
+fn copy_range(
+    in_f: &std::fs::File,
+    out_f: &std::fs::File,
+    off: u64,
+    len: u64,
+    p: &Progress,
+) -> Result<bool> {
+    use rustix::fs::copy_file_range;
+    use rustix::io::Errno;
+
+    let (mut off_in, mut off_out) = (off, off);
+    let end = off + len;
+    while off_in < end {
+        check_interrupt()?;
+        // Morceaux de 8 MiB: progression et Ctrl-C restent réactifs
+        let chunk = (end - off_in).min(CHUNK) as usize;
+        match copy_file_range(in_f, Some(&mut off_in), out_f, Some(&mut off_out), chunk) {
+            Ok(0) => break, // fichier raccourci pendant la copie
+            Ok(n) => p.add_bytes(n as u64),
+            Err(Errno::XDEV | Errno::NOSYS | Errno::INVAL | Errno::OPNOTSUPP | Errno::PERM)
+                if off_in == off =>
+            {
+                return Ok(false);
+            }
+            Err(Errno::INTR) => {}
+            Err(e) => return Err(e).context("copy_file_range"),
+        }
+    }
+    Ok(true)
+}
+
+/// Copie une zone avec pread/pwrite (repli quand `copy_file_range` est refusé)
+#[cfg(unix)]
+fn copy_extent(
+    in_f: &std::fs::File,
+    out_f: &std::fs::File,
+    off: u64,
+    len: u64,
+    p: &Progress,
+) -> Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    #[cfg(target_os = "linux")]
+    if copy_range(in_f, out_f, off, len, p)? {
+        return Ok(());
+    }
+    let mut buf = vec![0u8; len.min(1 << 20) as usize];
+    let (mut pos, end) = (off, off + len);
+    while pos < end {
+        check_interrupt()?;
+        let n = ((end - pos) as usize).min(buf.len());
+        in_f.read_exact_at(&mut buf[..n], pos).context("read data extent")?;
+        out_f.write_all_at(&buf[..n], pos).context("write data extent")?;
+        pos += n as u64;
+        p.add_bytes(n as u64);
+    }
+    Ok(())
+}
+
+/// Copie bufferisée jusqu'à EOF depuis les positions courantes
+fn copy_buffered(in_f: &mut fs::File, out_f: &mut fs::File, p: &Progress) -> Result<()> {
+    use std::io::{Read, Write};
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        check_interrupt()?;
+        let n = match in_f.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("read stream"),
+        };
+        out_f.write_all(&buf[..n]).context("write stream")?;
+        p.add_bytes(n as u64);
+    }
+}
+
+/// Copie un fichier creux zone de données par zone de données (SEEK_DATA/SEEK_HOLE):
+/// les trous restent des trous. `false` si le FS ne sait pas répondre (rien n'est écrit).
+#[cfg(unix)]
+fn copy_sparse(
+    in_f: &std::fs::File,
+    out_f: &std::fs::File,
+    len: u64,
+    p: &Progress,
+) -> Result<bool> {
+    use rustix::fs::{SeekFrom, seek};
+    use rustix::io::Errno;
+
+    let mut pos = 0;
+    while pos < len {
+        let data = match seek(in_f, SeekFrom::Data(pos)) {
+            Ok(d) => d,
+            Err(Errno::NXIO) => break, // plus que du vide jusqu'à la fin
+            Err(Errno::INVAL) if pos == 0 => return Ok(false),
+            Err(e) => return Err(e).context("lseek(SEEK_DATA)"),
+        };
+        let hole = seek(in_f, SeekFrom::Hole(data)).context("lseek(SEEK_HOLE)")?;
+        copy_extent(in_f, out_f, data, hole - data, p)?;
+        pos = hole;
+    }
+    // Trou final: la taille suffit
+    out_f.set_len(len).context("truncate(dst)")?;
+    Ok(true)
+}
+
+/// Contenu d'un fichier régulier, du plus rapide au plus sûr:
+/// FICLONE (reflink btrfs/XFS…, trous compris) → zones de données d'un fichier creux →
+/// `copy_file_range` → copie bufferisée
+fn copy_file_data(
+    in_f: &mut fs::File,
+    out_f: &mut fs::File,
+    meta: &std::fs::Metadata,
+    p: &Progress,
+) -> Result<()> {
+    #[cfg(target_os = "linux")]
+    if rustix::fs::ioctl_ficlone(out_f.file(), in_f.file()).is_ok() {
+        p.add_bytes(meta.len());
+        return Ok(());
+    }
+    // Moins de blocs alloués que d'octets: fichier creux
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if meta.blocks().saturating_mul(512) < meta.len()
+            && copy_sparse(in_f.file(), out_f.file(), meta.len(), p)?
+        {
+            return Ok(());
+        }
+    }
+    #[cfg(target_os = "linux")]
+    if copy_range(in_f.file(), out_f.file(), 0, meta.len(), p)? {
+        // Fichier grossi pendant la copie: la fin en bufferisé
+        use std::io::{Seek, SeekFrom};
+        in_f.seek(SeekFrom::Start(meta.len()))?;
+        out_f.seek(SeekFrom::Start(meta.len()))?;
+        return copy_buffered(in_f, out_f, p);
+    }
+    #[cfg(windows)]
+    let _ = meta;
+    copy_buffered(in_f, out_f, p)
+}
+
+fn copy_file_fsync(src: &Path, dst: &Path, meta: &std::fs::Metadata, p: &Progress) -> Result<()> {
     let mut in_f = fs::File::open(src).with_context(|| format!("open(src): {}", src.display()))?;
     let mut out_f =
         fs::File::create(dst).with_context(|| format!("create(dst): {}", dst.display()))?;
-    std::io::copy(&mut in_f, &mut out_f).context("copy stream")?;
+    copy_file_data(&mut in_f, &mut out_f, meta, p)?;
     out_f.sync_all().context("fsync(dst)")?;
     Ok(())
 }
 
+/// Recrée un FIFO, un socket ou un nœud de périphérique (le lire bloquerait ou échouerait)
+#[cfg(unix)]
+fn make_special(dst: &Path, meta: &std::fs::Metadata) -> Result<()> {
+    use rustix::fs::{CWD, FileType, Mode, mknodat};
+    use std::os::unix::fs::MetadataExt;
+
+    let ft = FileType::from_raw_mode(meta.mode());
+    let perm = Mode::from_raw_mode(meta.mode() & 0o7777);
+    match mknodat(CWD, dst, ft, perm, meta.rdev()) {
+        Ok(()) => Ok(()),
+        Err(rustix::io::Errno::PERM) => anyhow::bail!(
+            "mknod {}: recreating a {:?} needs CAP_MKNOD",
+            dst.display(),
+            ft
+        ),
+        Err(e) => Err(e).with_context(|| format!("mknod {} ({:?})", dst.display(), ft)),
+    }
+}
+
+/// Erreurs « attendues » en recopiant des métadonnées: droits insuffisants
+/// (chown non-root, `trusted.*`) ou FS cible sans support (xattrs)
+#[cfg(unix)]
+fn is_unsupported(e: rustix::io::Errno) -> bool {
+    use rustix::io::Errno;
+    e == Errno::PERM || e == Errno::NOTSUP || e == Errno::OPNOTSUPP || e == Errno::ACCESS
+}
+
+/// Recopie les attributs étendus (ACL POSIX comprises: `system.posix_acl_*`)
+#[cfg(unix)]
+fn copy_xattrs(src: &Path, dst: &Path) -> Result<()> {
+    use rustix::fs::{XattrFlags, lgetxattr, llistxattr, lsetxattr};
+    // 1er appel à vide: taille de la liste
+    let need = match llistxattr(src, &mut [0u8; 0][..]) {
+        Ok(0) => return Ok(()),
+        Ok(n) => n,
+        Err(e) if is_unsupported(e) => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("listxattr {}", src.display())),
+    };
+    let mut names = vec![0u8; need];
+    let n = llistxattr(src, &mut names[..])
+        .with_context(|| format!("listxattr {}", src.display()))?;
+    names.truncate(n);
+    for name in names.split(|&b| b == 0).filter(|n| !n.is_empty()) {
+        let name = std::ffi::OsStr::from_bytes(name);
+        let size = match lgetxattr(src, name, &mut [0u8; 0][..]) {
+            Ok(n) => n,
+            Err(e) if is_unsupported(e) => continue,
+            Err(e) => return Err(e).with_context(|| format!("getxattr {}", src.display())),
+        };
+        let mut value = vec![0u8; size];
+        let n = lgetxattr(src, name, &mut value[..])
+            .with_context(|| format!("getxattr {}", src.display()))?;
+        value.truncate(n);
+        match lsetxattr(dst, name, &value, XattrFlags::empty()) {
+            Ok(()) => {}
+            Err(e) if is_unsupported(e) => {}
+            Err(e) => return Err(e).with_context(|| format!("setxattr {}", dst.display())),
+        }
+    }
+    Ok(())
+}
+
+/// Recopie les métadonnées de `src` sur `dst` (déjà rempli), sans suivre les symlinks:
+/// propriétaire, xattrs, mode puis dates (en dernier: les étapes précédentes touchent ctime/mtime)
+#[cfg(unix)]
+fn copy_metadata(src: &Path, dst: &Path, meta: &std::fs::Metadata) -> Result<()> {
+    use rustix::fs::{AtFlags, CWD, Gid, Mode, Timespec, Timestamps, Uid, chmodat, chownat, utimensat};
+    use std::os::unix::fs::MetadataExt;
+
+    // chown d'abord (il efface setuid/setgid); sans privilège, au moins le groupe
+    let (uid, gid) = (Uid::from_raw(meta.uid()), Gid::from_raw(meta.gid()));
+    match chownat(CWD, dst, Some(uid), Some(gid), AtFlags::SYMLINK_NOFOLLOW) {
+        Ok(()) => {}
+        Err(e) if is_unsupported(e) => {
+            let _ = chownat(CWD, dst, None, Some(gid), AtFlags::SYMLINK_NOFOLLOW);
+        }
+        Err(e) => return Err(e).with_context(|| format!("chown {}", dst.display())),
+    }
+    copy_xattrs(src, dst)?;
+    if !meta.file_type().is_symlink() {
+        chmodat(CWD, dst, Mode::from_raw_mode(meta.mode() & 0o7777), AtFlags::empty())
+            .with_context(|| format!("chmod {}", dst.display()))?;
+    }
+    let times = Timestamps {
+        last_access: Timespec {
+            tv_sec: meta.atime(),
+            tv_nsec: meta.atime_nsec(),
+        },
+        last_modification: Timespec {
+            tv_sec: meta.mtime(),
+            tv_nsec: meta.mtime_nsec(),
+        },
+    };
+    utimensat(CWD, dst, &times, AtFlags::SYMLINK_NOFOLLOW)
+        .with_context(|| format!("utimensat {}", dst.display()))?;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn copy_metadata(_src: &Path, dst: &Path, meta: &std::fs::Metadata) -> Result<()> {
+    fs::set_permissions(dst, meta.permissions())?;
+    Ok(())
+}
+
+/// Copie récursive fidèle: contenu, puis mode, propriétaire, dates et xattrs de chaque nœud.
+/// Les liens durs internes à l'arbre restent des liens durs, les trous restent des trous,
+/// les fichiers spéciaux sont recréés.
 pub fn copy_recursively(src: &Path, dst: &Path) -> Result<()> {
+    let name = src.file_name().unwrap_or_default().to_string_lossy();
+    let label = parse_unique_name(&name).map_or(&*name, |(_, base)| base);
+    crate::progress::track(
+        label,
+        || crate::size::measure(src).apparent,
+        |p| {
+            let mut cx = CopyCtx {
+                links: HashMap::new(),
+                progress: p,
+            };
+            copy_tree(src, dst, &mut cx)
+        },
+    )
+}
+
+struct CopyCtx<'a> {
+    /// (dev, ino) source → première copie, pour les inodes à liens multiples
+    links: HashMap<(u64, u64), PathBuf>,
+    progress: &'a Progress,
+}
+
+fn copy_tree(src: &Path, dst: &Path, cx: &mut CopyCtx) -> Result<()> {
+    check_interrupt()?;
     let meta = fs::symlink_metadata(src)?;
-    if meta.is_dir() {
+    cx.progress.add_file();
+    let ft = meta.file_type();
+
+    #[cfg(unix)]
+    let inode = {
+        use std::os::unix::fs::MetadataExt;
+        (!ft.is_dir() && meta.nlink() > 1).then(|| (meta.dev(), meta.ino()))
+    };
+    #[cfg(windows)]
+    let inode: Option<(u64, u64)> = None;
+    if let Some(first) = inode.and_then(|k| cx.links.get(&k)) {
+        fs::hard_link(first, dst)?;
+        return Ok(());
+    }
+
+    if ft.is_dir() {
         fs::create_dir_all(dst)?;
         for entry in fs::read_dir(src)? {
             let entry = entry?;
-            copy_recursively(&entry.path(), &dst.join(entry.file_name()))?;
+            copy_tree(&entry.path(), &dst.join(entry.file_name()), cx)?;
         }
-        Ok(())
-    } else if meta.file_type().is_symlink() {
+        // Après les enfants: un dossier en lecture seule ou sa mtime resteraient sinon faux
+        return copy_metadata(src, dst, &meta);
+    }
+
+    if ft.is_symlink() {
         #[cfg(unix)]
         {
             let target = fs::read_link(src)?;
             symlink(target, dst)?;
-            Ok(())
         }
         #[cfg(windows)]
         {
@@ -76,11 +381,22 @@ pub fn copy_recursively(src: &Path, dst: &Path) -> Result<()> {
             } else {
                 src.parent().unwrap_or(Path::new(".")).join(target)
             };
-            copy_recursively(&real, dst)
+            return copy_tree(&real, dst, cx);
         }
+    } else if ft.is_file() {
+        copy_file_fsync(src, dst, &meta, cx.progress)?;
     } else {
-        copy_file_fsync(src, dst)
+        // Kind::Other: FIFO, socket, périphérique
+        #[cfg(unix)]
+        make_special(dst, &meta)?;
+        #[cfg(windows)]
+        anyhow::bail!("unsupported file type: {}", src.display());
+    }
+    copy_metadata(src, dst, &meta)?;
+    if let Some(k) = inode {
+        cx.links.insert(k, dst.to_path_buf());
     }
+    Ok(())
 }
 
 pub fn remove_recursively(p: &Path) -> Result<()> {
@@ -97,30 +413,72 @@ pub fn remove_recursively(p: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn safe_move_unique(src: &Path, dst_dir: &Path, basename: &OsString) -> Result<PathBuf> {
-    fs::create_dir_all(dst_dir).with_context(|| format!("create_dir_all {}", dst_dir.display()))?;
+/// Nom unique dans le graveyard: `YYYYMMDDTHHMMSS__RANDOM__basename`
+pub fn unique_name(basename: &OsStr) -> String {
     let ts = chrono::Local::now().format("%Y%m%dT%H%M%S");
-    let unique = format!(
+    format!(
         "{}__{}__{}",
         ts,
         rand_suffix(),
         Path::new(basename).to_string_lossy()
-    );
-    let dst = dst_dir.join(unique);
+    )
+}
+
+/// Inverse de `unique_name`: (date d'enterrement en secondes Unix, basename)
+pub fn parse_unique_name(name: &str) -> Option<(i64, &str)> {
+    let (ts, rest) = name.split_at_checked(15)?;
+    let rest = rest.strip_prefix("__")?;
+    let (rand, base) = rest.split_at_checked(8)?;
+    let base = base.strip_prefix("__").filter(|b| !b.is_empty())?;
+    if !rand.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+        return None;
+    }
+    let naive = chrono::NaiveDateTime::parse_from_str(ts, "%Y%m%dT%H%M%S").ok()?;
+    let when = naive.and_local_timezone(chrono::Local).earliest()?;
+    Some((when.timestamp(), base))
+}
+
+/// Copie temporaire d'un déplacement inter-device: `<dst>.copying`
+pub fn copying_path(dst: &Path) -> PathBuf {
+    let mut name = dst.file_name().unwrap_or_default().to_os_string();
+    name.push(".copying");
+    dst.with_file_name(name)
+}
+
+/// Déplace `src` vers `dst` (chemin complet): rename, ou copy → swap → remove sur EXDEV
+pub fn safe_move(src: &Path, dst: &Path) -> Result<()> {
+    move_impl(src, dst, true)
+}
 
-    match fs::rename(src, &dst) {
+/// Comme `safe_move`, sans fsync du dossier cible après un simple rename:
+/// l'appelant regroupe ces fsync (`fsync_dir`) à la fin d'un lot
+pub fn safe_move_deferred(src: &Path, dst: &Path) -> Result<()> {
+    move_impl(src, dst, false)
+}
+
+fn move_impl(src: &Path, dst: &Path, sync_dst: bool) -> Result<()> {
+    let dst_dir = dst.parent().unwrap_or(Path::new("."));
+    match fs::rename(src, dst) {
         Ok(()) => {
-            fsync_dir(dst_dir)?;
-            Ok(dst)
+            if sync_dst {
+                fsync_dir(dst_dir)?;
+            }
+            Ok(())
         }
         Err(e) if is_exdev(&e) => {
-            let tmp = dst.with_extension("copying");
-            copy_recursively(src, &tmp)?;
+            let tmp = copying_path(dst);
+            // Ctrl-C pendant la copie: on nettoie au lieu de mourir en laissant `.copying`
+            let _sigint = InterruptGuard::install();
+            if let Err(e) = copy_recursively(src, &tmp) {
+                // Source intacte: on ne laisse pas de copie partielle derrière
+                let _ = remove_recursively(&tmp);
+                return Err(e);
+            }
             fsync_dir(tmp.parent().unwrap_or(dst_dir))?;
-            fs::rename(&tmp, &dst).context("swap tmp->dst")?;
+            fs::rename(&tmp, dst).context("swap tmp->dst")?;
             fsync_dir(dst_dir)?;
             remove_recursively(src)?;
-            Ok(dst)
+            Ok(())
         }
         Err(e) => Err(e).with_context(|| format!("rename {} -> {}", src.display(), dst.display())),
     }
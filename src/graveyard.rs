@@ -3,20 +3,27 @@ use std::io::{self, Write};
 use std::path;
 use std::path::PathBuf;
 
+use crate::config::{CremateAllConfirm, QuotaPolicy, Storage};
 use crate::index::{Entry, Kind};
+use crate::size::{Usage, human_size};
 
 use anyhow::{Context, Result};
 use chrono::{Local, TimeZone, Utc};
 use fs_err as fs;
-use std::ffi::OsString;
-use yansi::{Color, Paint};
+use std::time::Duration;
+use yansi::{Color, Paint, Style};
 
-use crate::fs_safemove::safe_move_unique;
+use crate::fs_safemove::{fsync_dir, safe_move, safe_move_deferred, unique_name};
 use crate::safety::{SafetyCtx, guard_path};
+use crate::template::{Field, Template};
 
 use crate::index; // pour appeler les shims
+use crate::journal;
+use crate::output::{self, say};
+use crate::progress::{Cancelled, InterruptGuard, check_interrupt, is_cancelled};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-fn display_id(e: &index::Entry) -> String {
+pub(crate) fn display_id(e: &index::Entry) -> String {
     e.trashed_path
         .file_name()
         .and_then(|n| n.to_str())
@@ -25,6 +32,22 @@ fn display_id(e: &index::Entry) -> String {
         .unwrap_or_else(|| "-".to_string())
 }
 
+/// Entrées désignées par `TARGET`: sous-chaîne du nom d'origine, préfixe de l'ID,
+/// ou chemin d'origine exact (avec un `/`, relatif au dossier courant)
+pub(crate) fn match_target(entries: &[Entry], q0: &str) -> Vec<Entry> {
+    let q = q0.to_lowercase();
+    let exact = q0.contains('/').then(|| path::absolute(q0).ok()).flatten();
+    entries
+        .iter()
+        .filter(|&e| {
+            let base = index::basename_of_original(e).to_lowercase();
+            let id = display_id(e).to_lowercase(); // derived id
+            base.contains(&q) || id.starts_with(&q) || exact.as_ref() == Some(&e.original_path)
+        })
+        .cloned()
+        .collect()
+}
+
 fn kind_letter(k: Kind) -> char {
     match k {
         Kind::File => 'F',
@@ -34,7 +57,7 @@ fn kind_letter(k: Kind) -> char {
     }
 }
 
-fn kind_icon(k: Kind) -> &'static str {
+pub(crate) fn kind_icon(k: Kind) -> &'static str {
     match k {
         Kind::File => "📄",
         Kind::Dir => "📁",
@@ -44,7 +67,7 @@ fn kind_icon(k: Kind) -> &'static str {
 }
 
 /// Durée compacte (2 unités max) ex: "1m47s", "3h12m", "2d"
-fn compact_age(mut secs: u64) -> String {
+pub(crate) fn compact_age(mut secs: u64) -> String {
     const MIN: u64 = 60;
     const H: u64 = 60 * MIN;
     const D: u64 = 24 * H;
@@ -66,124 +89,559 @@ fn compact_age(mut secs: u64) -> String {
     if out.is_empty() { "0s".into() } else { out }
 }
 
-fn graveyard_dir() -> Result<PathBuf> {
-    Ok(crate::paths::data_dir()?.join("graveyard"))
+/// Affiche une invite sans retour à la ligne (sur stderr en mode `--json`)
+fn prompt(msg: &str) -> Result<()> {
+    if output::is_machine() {
+        eprint!("{msg}");
+        io::stderr().flush()?;
+    } else {
+        print!("{msg}");
+        io::stdout().flush()?;
+    }
+    Ok(())
 }
 
-fn journal_path() -> Result<PathBuf> {
-    Ok(graveyard_dir()?.join(".journal"))
+/// Invite `y/N` sur stdout; `true` si l'utilisateur tape `y`
+fn confirm_yes_no() -> Result<bool> {
+    prompt("Confirm (y/N): ")?;
+    let mut buf = String::new();
+    io::stdin().read_line(&mut buf)?;
+    Ok(buf.trim().to_lowercase() == "y")
 }
 
-fn append_journal(line: &str) -> Result<()> {
-    use std::io::Write;
-    let jp = journal_path()?;
-    let mut f = fs::OpenOptions::new().create(true).append(true).open(&jp)?;
-    writeln!(f, "{line}")?;
-    f.sync_all()?;
-    Ok(())
+/// Invite stricte: il faut taper `YES`
+fn confirm_type_yes() -> Result<bool> {
+    prompt("Type YES to confirm: ")?;
+    let mut buf = String::new();
+    io::stdin().read_line(&mut buf)?;
+    Ok(buf.trim() == "YES")
+}
+
+fn graveyard_dir() -> Result<PathBuf> {
+    crate::paths::graveyard_dir()
 }
 
 fn path_depth(p: &std::path::Path) -> usize {
     p.components().count()
 }
 
-///build a map from original_path -> (index position)
+///build a map from original_path -> (index position of its latest buried version)
 fn build_original_map(entries: &[index::Entry]) -> HashMap<PathBuf, usize> {
-    let mut map = HashMap::with_capacity(entries.len());
+    let mut map: HashMap<PathBuf, usize> = HashMap::with_capacity(entries.len());
     for (i, e) in entries.iter().enumerate() {
-        map.insert(e.original_path.clone(), i);
+        // À seconde égale, l'ordre de l'index (celui des enterrements) départage
+        match map.get(&e.original_path) {
+            Some(&j) if entries[j].deleted_at > e.deleted_at => {}
+            _ => {
+                map.insert(e.original_path.clone(), i);
+            }
+        }
     }
     map
 }
 
-pub fn resurrect(items: &[PathBuf]) -> Result<()> {
-    index::with_index_mut(|idx| {
-        for gy_path in items {
-            if let Some(pos) = idx.items.iter().position(|e| e.trashed_path == *gy_path) {
-                let original = idx.items[pos].original_path.clone();
+/// Que faire quand la destination d'une restauration est occupée (`--on-conflict`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnConflict {
+    /// Arrêter le lot; ce qui est déjà restauré le reste
+    #[default]
+    Fail,
+    /// Laisser l'élément au graveyard et passer au suivant
+    Skip,
+    /// Restaurer sous `nom (restored N).ext`
+    Rename,
+    /// Enterrer l'occupant, puis restaurer à sa place
+    OverwriteBury,
+}
 
-                append_journal(&format!(
-                    "RESTORE_PENDING\t{}\t{}",
-                    gy_path.display(),
-                    original.display()
-                ))?;
+impl std::str::FromStr for OnConflict {
+    type Err = anyhow::Error;
 
-                if let Some(parent) = original.parent() {
-                    fs::create_dir_all(parent)?;
-                }
-                if original.exists() {
-                    anyhow::bail!("Target already exists: {}", original.display());
-                }
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "fail" => Ok(OnConflict::Fail),
+            "skip" => Ok(OnConflict::Skip),
+            "rename" => Ok(OnConflict::Rename),
+            "overwrite-bury" => Ok(OnConflict::OverwriteBury),
+            other => anyhow::bail!("expected skip|rename|overwrite-bury|fail, got '{other}'"),
+        }
+    }
+}
 
-                match fs::rename(gy_path, &original) {
-                    Ok(()) => {}
-                    Err(e) if super::fs_safemove::is_exdev(&e) => {
-                        super::fs_safemove::copy_recursively(gy_path, &original)?;
-                        super::fs_safemove::remove_recursively(gy_path)?;
-                    }
-                    Err(e) => {
-                        return Err(e).with_context(|| {
-                            format!("rename {} -> {}", gy_path.display(), original.display())
-                        });
+/// Premier `nom (restored N).ext` libre, N à partir de 2
+fn free_name(target: &path::Path) -> PathBuf {
+    let stem = target.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = target
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| target.with_file_name(format!("{stem} (restored {n}){ext}")))
+        .find(|p| fs::symlink_metadata(p).is_err())
+        .expect("unbounded range")
+}
+
+/// Bilan d'un lot de restaurations
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Restored {
+    pub restored: usize,
+    pub skipped: usize,
+}
+
+pub fn resurrect(items: &[PathBuf], on_conflict: OnConflict) -> Result<Restored> {
+    resurrect_to(items, |e| e.original_path.clone(), on_conflict)
+}
+
+/// Restaure chaque élément vers `dest(entrée)` (`--to`). Une erreur arrête le lot,
+/// mais l'index enregistre ce qui a déjà été restauré avant de la remonter.
+/// Un dossier restauré sous un autre nom emmène ses enfants du lot avec lui.
+pub fn resurrect_to(
+    items: &[PathBuf],
+    dest: impl Fn(&Entry) -> PathBuf,
+    on_conflict: OnConflict,
+) -> Result<Restored> {
+    let mut cancelled = false;
+    let mut failure = None;
+    let mut done = Restored::default();
+    // (destination prévue, destination réelle) des éléments déplacés ailleurs
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    index::with_index_mut(|idx| {
+        for gy_path in items {
+            let Some(e) = idx.items.iter().find(|e| e.trashed_path == *gy_path) else {
+                continue;
+            };
+            let planned = dest(e);
+            let target = rebase(&moved, &planned);
+            match restore_one(idx, gy_path, target, on_conflict) {
+                Ok(Some(actual)) => {
+                    done.restored += 1;
+                    if actual != planned {
+                        moved.push((planned, actual));
                     }
                 }
+                Ok(None) => done.skipped += 1,
+                Err(e) if is_cancelled(&e) => {
+                    cancelled = true;
+                    break;
+                }
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    })?;
+    if cancelled {
+        return Err(Cancelled.into());
+    }
+    if let Some(e) = failure {
+        if done.restored > 0 {
+            eprintln!("note: {} item(s) restored before the error", done.restored);
+        }
+        return Err(e);
+    }
+    Ok(done)
+}
+
+/// `target` sous la destination réelle de son plus proche parent déplacé ailleurs
+fn rebase(moved: &[(PathBuf, PathBuf)], target: &path::Path) -> PathBuf {
+    moved
+        .iter()
+        .filter(|(from, _)| target != from && target.starts_with(from))
+        .max_by_key(|(from, _)| path_depth(from))
+        .map_or_else(
+            || target.to_path_buf(),
+            |(from, to)| to.join(target.strip_prefix(from).unwrap_or(target)),
+        )
+}
+
+/// Position de l'entrée enterrée sous `trashed`
+fn position_of(idx: &index::Index, trashed: &path::Path) -> Result<usize> {
+    idx.items
+        .iter()
+        .position(|e| e.trashed_path == trashed)
+        .with_context(|| format!("{} is no longer in the index", trashed.display()))
+}
+
+/// Restaure l'entrée `gy_path` vers `target`; la destination réelle, None si sauté
+/// (`--on-conflict=skip`)
+fn restore_one(
+    idx: &mut index::Index,
+    gy_path: &path::Path,
+    target: PathBuf,
+    on_conflict: OnConflict,
+) -> Result<Option<PathBuf>> {
+    let pos = position_of(idx, gy_path)?;
+    let name = index::basename_of_original(&idx.items[pos]);
+    let Some(target) = make_room(idx, pos, &name, target, on_conflict)? else {
+        return Ok(None);
+    };
+    move_back(idx, gy_path, &target)?;
+    // Retrouvée par son chemin: `make_room` a pu enterrer l'occupant entre-temps
+    let e = idx.items.remove(position_of(idx, gy_path)?);
+    output::moved("restored", &e, display_id(&e), &target);
+    Ok(Some(target))
+}
 
-                append_journal(&format!(
-                    "RESTORE_DONE\t{}\t{}",
-                    gy_path.display(),
-                    original.display()
-                ))?;
-                idx.items.remove(pos);
+/// Libère `target` selon `on_conflict`; None si l'élément `name` est sauté.
+/// Sous `overwrite-bury`, l'index gagne une entrée: `pos` n'est plus sûr après l'appel.
+fn make_room(
+    idx: &mut index::Index,
+    pos: usize,
+    name: &str,
+    target: PathBuf,
+    on_conflict: OnConflict,
+) -> Result<Option<PathBuf>> {
+    // symlink_metadata: un lien cassé occupe aussi la place
+    if fs::symlink_metadata(&target).is_err() {
+        return Ok(Some(target));
+    }
+    match on_conflict {
+        OnConflict::Fail => anyhow::bail!("Target already exists: {}", target.display()),
+        OnConflict::Skip => {
+            let e = &idx.items[pos];
+            say!("Skipped {name}: {} already exists", target.display());
+            output::moved("skipped", e, display_id(e), &target);
+            Ok(None)
+        }
+        OnConflict::Rename => {
+            let renamed = free_name(&target);
+            say!(
+                "{} already exists; restoring {name} as {}",
+                target.display(),
+                renamed.display()
+            );
+            Ok(Some(renamed))
+        }
+        OnConflict::OverwriteBury => {
+            // Même chemin qu'un `nrip <occupant>`: journalisé, indexé, annoncé
+            let gy = graveyard_dir()?;
+            let ctx = safety_ctx(&gy, false)?;
+            let usage = crate::size::measure(&target);
+            bury_one(idx, &target, &usage, &ctx, &gy)
+                .with_context(|| format!("bury current {}", target.display()))?;
+            Ok(Some(target))
+        }
+    }
+}
+
+/// Déplacement journalisé `src` (dans le graveyard) → `target`. Un échec autre que Ctrl-C
+/// est réglé par `journal::restore_failed` avant d'être remonté.
+fn move_back(idx: &mut index::Index, src: &path::Path, target: &path::Path) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    journal::append(&format!(
+        "RESTORE_PENDING\t{}\t{}",
+        src.display(),
+        target.display()
+    ))?;
+
+    // rename, ou copie via `<target>.copying` puis swap (EXDEV)
+    if let Err(e) = safe_move(src, target) {
+        if is_cancelled(&e) {
+            journal::append(&format!(
+                "RESTORE_ABORTED\t{}\t{}",
+                src.display(),
+                target.display()
+            ))?;
+        } else {
+            journal::restore_failed(idx, src, target)?;
+        }
+        return Err(e);
+    }
+
+    journal::append(&format!(
+        "RESTORE_DONE\t{}\t{}",
+        src.display(),
+        target.display()
+    ))
+}
+
+/// Extrait `rel` du dossier enterré `dir` (chemin dans le graveyard) vers `dest(rel)`.
+/// Le reste demeure enterré: l'entrée reste dans l'index, avec sa taille mise à jour.
+pub fn extract(
+    dir: &path::Path,
+    rels: &[PathBuf],
+    dest: impl Fn(&path::Path) -> PathBuf,
+    on_conflict: OnConflict,
+) -> Result<Restored> {
+    let mut cancelled = false;
+    let mut failure = None;
+    let mut done = Restored::default();
+    index::with_index_mut(|idx| {
+        if !idx.items.iter().any(|e| e.trashed_path == dir) {
+            anyhow::bail!("{} is no longer in the graveyard", dir.display());
+        }
+        for rel in rels {
+            match extract_one(idx, dir, rel, dest(rel), on_conflict) {
+                Ok(true) => done.restored += 1,
+                Ok(false) => done.skipped += 1,
+                Err(e) if is_cancelled(&e) => {
+                    cancelled = true;
+                    break;
+                }
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                }
             }
         }
         Ok(())
-    })
+    })?;
+    if cancelled {
+        return Err(Cancelled.into());
+    }
+    if let Some(e) = failure {
+        if done.restored > 0 {
+            eprintln!("note: {} item(s) restored before the error", done.restored);
+        }
+        return Err(e);
+    }
+    Ok(done)
+}
+
+fn extract_one(
+    idx: &mut index::Index,
+    dir: &path::Path,
+    rel: &path::Path,
+    target: PathBuf,
+    on_conflict: OnConflict,
+) -> Result<bool> {
+    let pos = position_of(idx, dir)?;
+    let Some(inner) = inner_path(dir, rel) else {
+        anyhow::bail!("{}: not a path inside a buried directory", rel.display());
+    };
+    if fs::symlink_metadata(&inner).is_err() {
+        anyhow::bail!(
+            "{} is not inside the buried {}",
+            rel.display(),
+            idx.items[pos].original_path.display()
+        );
+    }
+    let name = rel.display().to_string();
+    let Some(target) = make_room(idx, pos, &name, target, on_conflict)? else {
+        return Ok(false);
+    };
+    move_back(idx, &inner, &target)?;
+    // Retrouvée par son chemin: `make_room` a pu enterrer l'occupant entre-temps
+    let pos = position_of(idx, dir)?;
+    let e = &mut idx.items[pos];
+    let usage = crate::size::measure(&e.trashed_path);
+    e.size = Some(usage.apparent);
+    e.disk_usage = Some(usage.disk);
+    say!("Restored {name} to {}", target.display());
+    output::extracted(e, display_id(e), rel, &target);
+    Ok(true)
 }
 
-pub fn resurrect_cmd(target: Option<String>, dry_run: bool, yes: bool) -> anyhow::Result<()> {
-    use std::io::{self, Write};
+/// `--to`: destination de chaque entrée. Un seul élément vers un chemin qui n'est pas un
+/// dossier existant: ce chemin tel quel. Sinon sous `to`, en gardant la hiérarchie relative
+/// au plus proche dossier commun des chemins d'origine.
+fn relocate(entries: &[Entry], to: &path::Path) -> HashMap<PathBuf, PathBuf> {
+    let originals: Vec<&path::Path> = entries.iter().map(|e| e.original_path.as_path()).collect();
+    entries
+        .iter()
+        .map(|e| e.trashed_path.clone())
+        .zip(relocate_paths(&originals, to))
+        .collect()
+}
 
-    let entries = index::load_entries().unwrap_or_default();
+/// Destinations de `paths` sous `to`, dans le même ordre (voir `relocate`)
+fn relocate_paths(paths: &[&path::Path], to: &path::Path) -> Vec<PathBuf> {
+    if paths.len() == 1 && !to.is_dir() {
+        return vec![to.to_path_buf()];
+    }
+    let mut common = paths
+        .first()
+        .and_then(|p| p.parent())
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default();
+    for p in paths {
+        while !p.starts_with(&common) && common.pop() {}
+    }
+    paths
+        .iter()
+        .map(|p| to.join(p.strip_prefix(&common).unwrap_or(p)))
+        .collect()
+}
+
+/// `dir.join(rel)` si `rel` ne fait que descendre dans `dir` (ni absolu, ni `..`)
+fn inner_path(dir: &path::Path, rel: &path::Path) -> Option<PathBuf> {
+    let normal = rel.components().all(|c| matches!(c, path::Component::Normal(_)));
+    let inner = dir.join(rel);
+    (normal && !rel.as_os_str().is_empty() && inner.starts_with(dir)).then_some(inner)
+}
+
+/// `project/src/main.rs` (relatif au dossier courant, ou absolu), ou `ID/src/main.rs`:
+/// un chemin sous un dossier enterré. Le dossier le plus profond qui le contient
+/// l'emporte, le plus récent à égalité.
+pub(crate) fn find_inner(entries: &[Entry], q: &str) -> Option<(usize, PathBuf)> {
+    let exists = |i: usize, rel: &path::Path| {
+        inner_path(&entries[i].trashed_path, rel).is_some_and(|p| fs::symlink_metadata(p).is_ok())
+    };
+    let dirs = || entries.iter().enumerate().filter(|(_, e)| e.kind == Kind::Dir);
+    if let Ok(p) = path::absolute(q)
+        && let Some((i, rel)) = dirs()
+            .filter_map(|(i, e)| Some((i, p.strip_prefix(&e.original_path).ok()?.to_path_buf())))
+            .filter(|(i, rel)| exists(*i, rel))
+            .max_by_key(|(i, _)| (path_depth(&entries[*i].original_path), entries[*i].deleted_at))
+    {
+        return Some((i, rel));
+    }
+    let (head, rel) = q.split_once('/')?;
+    let head = head.to_lowercase();
+    let rel = PathBuf::from(rel.trim_end_matches('/'));
+    let mut by_id = dirs().filter(|(i, e)| {
+        !head.is_empty() && display_id(e).to_lowercase().starts_with(&head) && exists(*i, &rel)
+    });
+    match (by_id.next(), by_id.next()) {
+        (Some((i, _)), None) => Some((i, rel)),
+        _ => None,
+    }
+}
+
+/// Restaure `rels` depuis le dossier enterré `e`, qui reste au graveyard
+fn resurrect_inside(
+    e: &Entry,
+    rels: Vec<PathBuf>,
+    to: Option<PathBuf>,
+    on_conflict: OnConflict,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let dests: HashMap<PathBuf, PathBuf> = match &to {
+        Some(to) => {
+            let originals: Vec<PathBuf> = rels.iter().map(|r| e.original_path.join(r)).collect();
+            let originals: Vec<&path::Path> = originals.iter().map(|p| p.as_path()).collect();
+            rels.iter().cloned().zip(relocate_paths(&originals, to)).collect()
+        }
+        None => rels.iter().map(|r| (r.clone(), e.original_path.join(r))).collect(),
+    };
+    say!(
+        "About to restore {} path(s) from the buried {} ({}); the rest stays buried:",
+        rels.len(),
+        e.original_path.display(),
+        display_id(e)
+    );
+    for r in rels.iter().take(10) {
+        say!("  {} -> {}", r.display(), dests[r].display());
+    }
+    if rels.len() > 10 {
+        say!("  ...");
+    }
+    if dry_run {
+        say!("--dry-run: nothing restored.");
+        return Ok(());
+    }
+    let ask = crate::config::get().confirm.resurrect;
+    if !yes && ask && rels.len() == 1 && !confirm_yes_no()? {
+        say!("Aborted.");
+        return Ok(());
+    }
+    let done = extract(&e.trashed_path, &rels, |r| dests[r].clone(), on_conflict)?;
+    say!("Restored {} item(s).", done.restored);
+    if done.skipped > 0 {
+        say!("Skipped {} item(s) whose target already exists.", done.skipped);
+    }
+    Ok(())
+}
+
+/// `p` avec son plus proche ancêtre existant résolu (symlinks compris)
+fn resolve_existing(p: &path::Path) -> PathBuf {
+    for a in p.ancestors() {
+        if let Ok(real) = std::fs::canonicalize(a) {
+            return real.join(p.strip_prefix(a).unwrap_or(p));
+        }
+    }
+    p.to_path_buf()
+}
+
+/// `--to` absolu, hors de tout ce qui détient des entrées: graveyard principal, ceux des
+/// montages (`e.graveyard`, `.nrip-graveyard-*`) et les corbeilles FreeDesktop
+fn checked_to(to: &path::Path, entries: &[Entry]) -> Result<PathBuf> {
+    let to = path::absolute(to).with_context(|| format!("absolutize {}", to.display()))?;
+    let mut holders = vec![graveyard_dir()?];
+    holders.extend(entries.iter().filter_map(|e| e.graveyard.clone()));
+    holders.extend(crate::freedesktop::trash_dirs()?.into_iter().map(|(t, _)| t));
+    let real = resolve_existing(&to);
+    let inside = holders
+        .iter()
+        .find(|h| to.starts_with(h) || real.starts_with(resolve_existing(h)));
+    if let Some(h) = inside {
+        anyhow::bail!("--to: cannot restore into the graveyard ({})", h.display());
+    }
+    if crate::mounts::in_mount_graveyard(&real) {
+        anyhow::bail!("--to: cannot restore into the graveyard ({})", to.display());
+    }
+    Ok(to)
+}
+
+pub fn resurrect_cmd(
+    target: Option<String>,
+    to: Option<PathBuf>,
+    on_conflict: OnConflict,
+    version: Option<crate::history::Version>,
+    all_generations: bool,
+    dry_run: bool,
+    yes: bool,
+) -> anyhow::Result<()> {
+
+    let entries = index::load_entries()?;
     let original_map = build_original_map(&entries);
+    let to = to.map(|t| checked_to(&t, &entries)).transpose()?;
 
     // 1) Construire la sélection (to_restore)
     let to_restore: Vec<index::Entry> = if let Some(ref q0) = target {
-        let q = q0.to_lowercase();
-
-        let matches: Vec<index::Entry> = entries
-            .iter()
-            .filter(|&e| {
-                let base = index::basename_of_original(e).to_lowercase();
-                let id = display_id(e).to_lowercase(); // derived id
-                base.contains(&q) || id.starts_with(&q)
-            })
-            .cloned()
-            .collect();
+        // Un chemin sous un dossier enterré: on n'en sort que ce chemin
+        if version.is_none()
+            && q0.contains('/')
+            && let Some((i, rel)) = find_inner(&entries, q0)
+        {
+            return resurrect_inside(&entries[i], vec![rel], to, on_conflict, dry_run, yes);
+        }
+        let matches = match_target(&entries, q0);
 
         if matches.is_empty() {
-            println!("No graveyard entry matches '{q0}'.");
+            say!("No graveyard entry matches '{q0}'.");
             return Ok(());
         }
+        // Versions d'un même chemin: une seule (`--generation`, `--as-of`, sinon la dernière)
+        let matches = crate::history::narrow(matches, version, all_generations)?;
         if matches.len() > 1 && !yes {
-            println!("Multiple matches (use TAB completion or add -y to restore all of them):");
+            say!("Multiple matches (use TAB completion or add -y to restore all of them):");
             for m in &matches {
                 let id = display_id(m);
-                println!("  {:7}  {}", id, index::basename_of_original(m));
+                say!("  {:7}  {}", id, index::basename_of_original(m));
             }
             return Ok(());
         }
         matches
     } else {
         // --- MODE INTERACTIF (fzf) ---
+        if version.is_some() {
+            anyhow::bail!("--generation/--as-of need a TARGET");
+        }
         let idx = index::load_index()?;
         if idx.items.is_empty() {
-            println!("Graveyard is empty.");
+            say!("Graveyard is empty.");
             return Ok(());
         }
 
-        let picks = crate::ui::pick_entries_with_fzf(&idx, /*preview=*/ false)?;
+        let picks = match crate::ui::pick_for_restore(&idx)? {
+            crate::ui::Pick::Entries(picks) => picks,
+            crate::ui::Pick::Inside(_, rels) if rels.is_empty() => {
+                say!("Aborted.");
+                return Ok(());
+            }
+            crate::ui::Pick::Inside(i, rels) => {
+                return resurrect_inside(&idx.items[i], rels, to, on_conflict, dry_run, yes);
+            }
+        };
         if picks.is_empty() {
-            println!("Aborted.");
+            say!("Aborted.");
             return Ok(());
         }
 
@@ -219,211 +677,796 @@ pub fn resurrect_cmd(target: Option<String>, dry_run: bool, yes: bool) -> anyhow
     }
 
     // Reconstruire la liste finale d'entrées à restaurer (parents inclus)
-    let mut final_list: Vec<index::Entry> = Vec::with_capacity(wanted.len());
+    // (un parent ajouté l'est dans sa dernière version)
+    let mut final_list: Vec<index::Entry> = to_restore.clone();
     let mut auto_added: Vec<PathBuf> = Vec::new();
     for p in &wanted {
         if let Some(&i) = original_map.get(p) {
             // si pas déjà dans to_restore explicite, on note qu'on l'a ajouté
             if !to_restore.iter().any(|e| &e.original_path == p) {
                 auto_added.push(p.clone());
+                final_list.push(entries[i].clone());
             }
-            final_list.push(entries[i].clone());
         }
     }
-    // Trier par profondeur croissante (parents → enfants)
-    final_list.sort_by_key(|e| path_depth(&e.original_path));
+    // Trier par profondeur croissante (parents → enfants), puis du plus ancien au plus récent
+    final_list.sort_by_key(|e| (path_depth(&e.original_path), e.deleted_at));
 
     if !auto_added.is_empty() {
-        println!(
+        say!(
             "Including {} parent path(s) for consistency:",
             auto_added.len()
         );
         for p in auto_added.iter().take(10) {
             // évite le spam
-            println!("  {}", p.display());
+            say!("  {}", p.display());
         }
         if auto_added.len() > 10 {
-            println!("  ...");
+            say!("  ...");
         }
     }
 
+    // 1.c) Destinations (`--to`), montrées avant toute confirmation
+    let relocated = match &to {
+        Some(to) => {
+            let plan = relocate(&final_list, to);
+            say!("Restoring to {}:", to.display());
+            for e in final_list.iter().take(10) {
+                say!("  {} -> {}", index::basename_of_original(e), plan[&e.trashed_path].display());
+            }
+            if final_list.len() > 10 {
+                say!("  ...");
+            }
+            Some(plan)
+        }
+        None => None,
+    };
+
     // 2) Bilan & confirmations
     let is_all = final_list.len() == entries.len();
     if is_all {
-        println!(
+        say!(
             "About to restore ALL graveyard items: {} item(s).",
             final_list.len()
         );
         if dry_run {
-            println!("--dry-run: nothing restored.");
+            say!("--dry-run: nothing restored.");
             return Ok(());
         }
-        if !yes {
-            print!("Type YES to confirm: ");
-            io::stdout().flush()?;
-            let mut buf = String::new();
-            io::stdin().read_line(&mut buf)?;
-            if buf.trim() != "YES" {
-                println!("Aborted.");
-                return Ok(());
-            }
+        if !yes && !confirm_type_yes()? {
+            say!("Aborted.");
+            return Ok(());
         }
     } else {
-        println!("About to restore {} item(s).", to_restore.len());
+        say!("About to restore {} item(s).", to_restore.len());
         if dry_run {
-            println!("--dry-run: nothing restored.");
+            say!("--dry-run: nothing restored.");
             return Ok(());
         }
-        if !yes && to_restore.len() == 1 {
-            print!("Confirm (y/N): ");
-            io::stdout().flush()?;
-            let mut buf = String::new();
-            io::stdin().read_line(&mut buf)?;
-            if buf.trim().to_lowercase() != "y" {
-                println!("Aborted.");
-                return Ok(());
-            }
+        let ask = crate::config::get().confirm.resurrect;
+        if !yes && ask && to_restore.len() == 1 && !confirm_yes_no()? {
+            say!("Aborted.");
+            return Ok(());
         }
     }
 
     // 3) Exécution
     let paths: Vec<PathBuf> = final_list.iter().map(|e| e.trashed_path.clone()).collect();
     if paths.is_empty() {
-        println!("Nothing to restore.");
+        say!("Nothing to restore.");
         return Ok(());
     }
 
     // On réutilise ta fonction existante (journal, checks, msg "Restored to ...")
-    resurrect(&paths)?;
+    let done = match relocated {
+        Some(plan) => resurrect_to(&paths, |e| plan[&e.trashed_path].clone(), on_conflict)?,
+        None => resurrect(&paths, on_conflict)?,
+    };
 
-    println!("Restored {} item(s).", paths.len());
+    say!("Restored {} item(s).", done.restored);
+    if done.skipped > 0 {
+        say!("Skipped {} item(s) whose target already exists.", done.skipped);
+    }
     Ok(())
 }
 
-pub fn bury(paths: &[PathBuf], force: bool) -> Result<()> {
-    let gy = graveyard_dir()?;
-    let ctx = SafetyCtx {
-        graveyard: gy.clone(),
+/// Le quota ne porte que sur le graveyard principal (ou la corbeille du home): ceux des
+/// autres points de montage occupent un autre système de fichiers
+fn under_quota(e: &Entry) -> bool {
+    e.graveyard.is_none()
+}
+
+/// Occupation comptée par le quota: (octets, éléments)
+fn quota_usage(entries: &[Entry]) -> (u64, usize) {
+    entries
+        .iter()
+        .filter(|e| under_quota(e))
+        .fold((0, 0), |(used, n), e| {
+            (used.saturating_add(crate::size::entry_usage(e).apparent), n + 1)
+        })
+}
+
+/// `[quota]` avant tout déplacement: ce qui entre (`incoming` octets, `count` éléments)
+/// doit tenir seul, et sous `refuse` le graveyard ne doit pas déborder.
+fn check_quota(idx: &index::Index, incoming: u64, count: usize) -> Result<()> {
+    let q = &crate::config::get().quota;
+    let max_bytes = q.max_bytes();
+    if max_bytes.is_none() && q.max_items.is_none() {
+        return Ok(());
+    }
+    if let Some(max) = max_bytes
+        && incoming > max
+    {
+        anyhow::bail!(
+            "quota: {} to bury exceeds quota.max_size ({})",
+            crate::size::human_size(incoming),
+            crate::size::human_size(max)
+        );
+    }
+    if let Some(max) = q.max_items
+        && count > max
+    {
+        anyhow::bail!("quota: {count} item(s) to bury exceeds quota.max_items ({max})");
+    }
+    if q.policy != QuotaPolicy::Refuse {
+        return Ok(());
+    }
+    let (used, items) = quota_usage(&idx.items);
+    if max_bytes.is_some_and(|m| used.saturating_add(incoming) > m)
+        || q.max_items.is_some_and(|m| items + count > m)
+    {
+        anyhow::bail!(
+            "quota exceeded: graveyard holds {} in {items} item(s); burying {} more is refused (quota.policy = \"refuse\")",
+            crate::size::human_size(used),
+            crate::size::human_size(incoming)
+        );
+    }
+    Ok(())
+}
+
+/// Éviction `[quota]`, une fois les nouveaux venus (`idx.items[fresh..]`) enterrés: les plus
+/// anciennes entrées partent jusqu'à ce qu'ils tiennent. Un enterrement raté ou annulé
+/// n'évince donc rien.
+fn enforce_quota(idx: &mut index::Index, fresh: usize) -> Result<()> {
+    let q = &crate::config::get().quota;
+    let max_bytes = q.max_bytes();
+    if (max_bytes.is_none() && q.max_items.is_none()) || q.policy == QuotaPolicy::Refuse {
+        return Ok(());
+    }
+    let (incoming, count) = quota_usage(&idx.items[fresh..]);
+    let over = |used: u64, items: usize| {
+        max_bytes.is_some_and(|m| used.saturating_add(incoming) > m)
+            || q.max_items.is_some_and(|m| items + count > m)
+    };
+
+    let mut sizes: HashMap<PathBuf, u64> = HashMap::with_capacity(fresh);
+    let mut used: u64 = 0;
+    for e in idx.items[..fresh].iter().filter(|e| under_quota(e)) {
+        let sz = crate::size::entry_usage(e).apparent;
+        used = used.saturating_add(sz);
+        sizes.insert(e.trashed_path.clone(), sz);
+    }
+    let mut items = sizes.len();
+    if !over(used, items) {
+        return Ok(());
+    }
+
+    // Évincer les plus anciens d'abord
+    let mut order: Vec<usize> = (0..fresh).filter(|&i| under_quota(&idx.items[i])).collect();
+    order.sort_by_key(|&i| idx.items[i].deleted_at);
+    let mut evicted: HashSet<PathBuf> = HashSet::new();
+    for i in order {
+        if !over(used, items) {
+            break;
+        }
+        let e = &idx.items[i];
+        // Journalisé: un arrêt brutal pendant la suppression est achevé au prochain rejeu
+        journal::append(&format!("EVICT\t{}", e.trashed_path.display()))?;
+        if let Err(err) = remove_trashed(&e.trashed_path) {
+            eprintln!("warn: cannot evict {}: {}", e.trashed_path.display(), err);
+            continue;
+        }
+        let sz = sizes.get(&e.trashed_path).copied().unwrap_or(0);
+        used = used.saturating_sub(sz);
+        items -= 1;
+        evicted.insert(e.trashed_path.clone());
+        output::done("evicted", e, display_id(e));
+        say!(
+            "{} {}",
+            Paint::new("🔥").fg(Color::Red),
+            Paint::new(format!(
+                "{} cremated to make room ({})",
+                index::basename_of_original(e),
+                crate::size::human_size(sz)
+            ))
+            .dim()
+        );
+    }
+    idx.items.retain(|e| !evicted.contains(&e.trashed_path));
+    if over(used, items) {
+        eprintln!("warn: quota: cannot free enough room; the graveyard stays over quota");
+    }
+    Ok(())
+}
+
+/// Garde-fous d'un enterrement vers `gy` (ou la corbeille FreeDesktop)
+fn safety_ctx(gy: &path::Path, force: bool) -> Result<SafetyCtx> {
+    Ok(SafetyCtx {
+        graveyard: match crate::config::get().storage {
+            Storage::Graveyard => gy.to_path_buf(),
+            Storage::Freedesktop => crate::freedesktop::home_trash()?,
+        },
         preserve_root: true,
         force,
+    })
+}
+
+pub fn bury(paths: &[PathBuf], force: bool, jobs: usize) -> Result<()> {
+    let gy = graveyard_dir()?;
+    let ctx = safety_ctx(&gy, force)?;
+    let jobs = match jobs {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
     };
+    let parallel = jobs > 1 && paths.len() > 1;
 
+    // Mesure avant déplacement (sert au quota et à l'index), hors verrou
+    let usages = measure_all(paths, if parallel { jobs } else { 1 });
+
+    let mut cancelled = false;
+    let mut failed = 0;
+    let mut failure = None;
     index::with_index_mut(|idx| {
-        for src in paths {
-            let original_abs =
-                path::absolute(src).with_context(|| format!("absolutize {}", src.display()))?;
-            guard_path(&original_abs, &ctx)?;
-            let base: OsString = src.file_name().unwrap_or_default().to_os_string();
-
-            append_journal(&format!(
-                "PENDING\t{}\t{}",
-                original_abs.display(),
-                base.to_string_lossy()
-            ))?;
-            // Détection du "kind" au moment du déplacement (fiable)
-            let md = fs::symlink_metadata(src)?;
-            let kind = if md.file_type().is_dir() {
-                Kind::Dir
-            } else if md.file_type().is_file() {
-                Kind::File
-            } else if md.file_type().is_symlink() {
-                Kind::Symlink
-            } else {
-                Kind::Other
-            };
+        // Garde-fous et cibles d'abord: rien n'est évincé pour un enterrement refusé.
+        // Un échec, ici ou au déplacement, n'arrête pas le lot (`-j1` comme `-jN`).
+        let mut slots = Vec::with_capacity(paths.len());
+        for (src, usage) in paths.iter().zip(&usages) {
+            match prepare(src, usage, &ctx, &gy) {
+                Ok(slot) => slots.push(slot),
+                Err(e) => {
+                    report_failure(src, &e);
+                    failed += 1;
+                }
+            }
+        }
 
-            let dst = safe_move_unique(src, &gy, &base)
-                .with_context(|| format!("move {} -> graveyard", src.display()))?;
-            append_journal(&format!(
-                "DONE\t{}\t{}",
-                original_abs.display(),
-                dst.display()
-            ))?;
+        // Les erreurs sont remontées après la transaction: ce qui est enterré reste indexé
+        let quota_bound = slots.iter().filter(|s| s.holder.is_none());
+        let (incoming, count) = quota_bound.fold((0u64, 0), |(b, n), s| {
+            (b.saturating_add(s.usage.apparent), n + 1)
+        });
+        if let Err(e) = check_quota(idx, incoming, count) {
+            release_all(slots);
+            failure = Some(e);
+            return Ok(());
+        }
 
-            idx.items.push(Entry {
-                original_path: original_abs,
-                trashed_path: dst,
-                deleted_at: Utc::now().timestamp(),
-                kind,
-            });
-            // Petit retour utilisateur : "<name> buried" avec icône
-            let name = base.to_string_lossy();
-            // Couleurs sobres (gérées par yansi::enable/disable dans main.rs)
-            println!(
-                "{} {}",
-                Paint::new("🪦").fg(Color::Magenta),
-                Paint::new(format!("{name} buried")).bold()
-            );
+        let fresh = idx.items.len();
+        if parallel {
+            match bury_parallel(idx, slots, jobs) {
+                Ok((n, interrupted)) => (failed, cancelled) = (failed + n, interrupted),
+                Err(e) => failure = Some(e),
+            }
+        } else {
+            let mut slots = slots.into_iter();
+            for slot in slots.by_ref() {
+                let src = slot.src;
+                match bury_slot(idx, slot) {
+                    Ok(()) => {}
+                    // Ctrl-C: copie partielle déjà supprimée, source intacte; on garde ce qui est fait
+                    Err(e) if is_cancelled(&e) => {
+                        cancelled = true;
+                        break;
+                    }
+                    Err(e) => {
+                        report_failure(src, &e);
+                        failed += 1;
+                    }
+                }
+            }
+            release_all(slots.collect());
+        }
+
+        // Place faite seulement pour ce qui est vraiment entré, et pas après un Ctrl-C
+        if !cancelled
+            && failure.is_none()
+            && let Err(e) = enforce_quota(idx, fresh)
+        {
+            failure = Some(e);
         }
         Ok(())
+    })?;
+    if cancelled {
+        return Err(Cancelled.into());
+    }
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} path(s) could not be buried", paths.len());
+    }
+    Ok(())
+}
+
+/// Tailles de `paths` (récursives), parcourues sur `jobs` fils
+fn measure_all(paths: &[PathBuf], jobs: usize) -> Vec<Usage> {
+    if jobs <= 1 {
+        return paths.iter().map(|p| crate::size::measure(p)).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut usages = vec![Usage::default(); paths.len()];
+    std::thread::scope(|scope| {
+        let next = &next;
+        let workers: Vec<_> = (0..jobs.min(paths.len()))
+            .map(|_| {
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(p) = paths.get(i) else { break };
+                        done.push((i, crate::size::measure(p)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        for w in workers {
+            for (i, usage) in w.join().expect("measure worker panicked") {
+                usages[i] = usage;
+            }
+        }
+    });
+    usages
+}
+
+/// Enterre `src` dans la transaction en cours (garde-fous, journal, index, annonce)
+fn bury_one(
+    idx: &mut index::Index,
+    src: &path::Path,
+    usage: &Usage,
+    ctx: &SafetyCtx,
+    gy: &path::Path,
+) -> Result<()> {
+    let slot = prepare(src, usage, ctx, gy)?;
+    bury_slot(idx, slot)
+}
+
+/// Déplace un enterrement préparé (journal, index, annonce).
+/// Ctrl-C pendant une copie: `ABORTED` journalisé, erreur `Cancelled`. Autre échec: réglé
+/// par `journal::bury_failed` (cible déjà créée adoptée), puis remonté.
+fn bury_slot(idx: &mut index::Index, slot: Slot<'_>) -> Result<()> {
+    // PENDING porte le chemin cible complet: de quoi rejouer après un crash
+    journal::append(&format!(
+        "PENDING\t{}\t{}",
+        slot.original.display(),
+        slot.dst.display()
+    ))?;
+    let moved = match slot.reservation {
+        Some(r) => r
+            .fill(slot.src)
+            .map(drop)
+            .with_context(|| format!("move {} -> trash", slot.src.display())),
+        None => safe_move(slot.src, &slot.dst)
+            .with_context(|| format!("move {} -> graveyard", slot.src.display())),
+    };
+    match moved {
+        Err(e) if is_cancelled(&e) => {
+            journal::append(&format!(
+                "ABORTED\t{}\t{}",
+                slot.original.display(),
+                slot.dst.display()
+            ))?;
+            return Err(e);
+        }
+        Err(e) => {
+            journal::bury_failed(idx, &slot.original, &slot.dst)?;
+            return Err(e);
+        }
+        Ok(()) => {}
+    }
+    let done = format!("DONE\t{}\t{}", slot.original.display(), slot.dst.display());
+
+    // Indexé avant le `DONE`: si le journal ne suit plus, l'entrée n'est pas perdue
+    let entry = buried_entry(slot.original, slot.dst, slot.kind, slot.holder, slot.usage);
+    announce_buried(&entry);
+    idx.items.push(entry);
+    journal::append(&done)
+}
+
+fn buried_entry(
+    original: PathBuf,
+    trashed: PathBuf,
+    kind: Kind,
+    holder: Option<PathBuf>,
+    usage: &Usage,
+) -> Entry {
+    Entry {
+        original_path: original,
+        trashed_path: trashed,
+        deleted_at: Utc::now().timestamp(),
+        kind,
+        graveyard: holder,
+        size: Some(usage.apparent),
+        disk_usage: Some(usage.disk),
+        extra: Default::default(),
+    }
+}
+
+/// Petit retour utilisateur : "<name> buried" avec icône
+fn announce_buried(e: &Entry) {
+    output::done("buried", e, display_id(e));
+    let name = index::basename_of_original(e);
+    // Couleurs sobres (gérées par yansi::enable/disable dans main.rs)
+    say!(
+        "{} {}",
+        Paint::new("🪦").fg(Color::Magenta),
+        Paint::new(format!("{name} buried")).bold()
+    );
+}
+
+/// Enterrement préparé: cible choisie (ou `.trashinfo` réservé), rien de déplacé
+struct Slot<'a> {
+    src: &'a path::Path,
+    original: PathBuf,
+    dst: PathBuf,
+    kind: Kind,
+    holder: Option<PathBuf>,
+    usage: &'a Usage,
+    reservation: Option<crate::freedesktop::Reservation>,
+}
+
+fn prepare<'a>(
+    src: &'a path::Path,
+    usage: &'a Usage,
+    ctx: &SafetyCtx,
+    gy: &path::Path,
+) -> Result<Slot<'a>> {
+    let original =
+        path::absolute(src).with_context(|| format!("absolutize {}", src.display()))?;
+    guard_path(&original, ctx)?;
+    let kind = Kind::from_file_type(fs::symlink_metadata(src)?.file_type());
+    let (dst, holder, reservation) = match crate::config::get().storage {
+        Storage::Graveyard => {
+            let target_gy = crate::mounts::graveyard_for(&original, gy)?;
+            fs::create_dir_all(&target_gy)?;
+            let dst = target_gy.join(unique_name(src.file_name().unwrap_or_default()));
+            (dst, (target_gy != gy).then_some(target_gy), None)
+        }
+        Storage::Freedesktop => {
+            let r = crate::freedesktop::reserve(&original)?;
+            (r.dst.clone(), r.holder.clone(), Some(r))
+        }
+    };
+    Ok(Slot {
+        src,
+        original,
+        dst,
+        kind,
+        holder,
+        usage,
+        reservation,
     })
 }
 
-pub fn list() -> anyhow::Result<()> {
-    let entries = index::load_entries().unwrap_or_default();
+/// Abandonne des enterrements préparés jamais commencés (réservations FreeDesktop)
+fn release_all(slots: Vec<Slot<'_>>) {
+    for r in slots.into_iter().filter_map(|s| s.reservation) {
+        r.release();
+    }
+}
+
+fn report_failure(src: &path::Path, err: &anyhow::Error) {
+    output::failed(src, err);
+    eprintln!("error: cannot bury {}: {err:#}", src.display());
+}
+
+/// Enterrement concurrent (`--jobs`): `PENDING` des cibles préparées journalisés d'un bloc,
+/// déplacements sur `jobs` fils, un fsync par dossier cible et un seul pour les `DONE`.
+/// Un échec n'arrête pas le lot; renvoie (échecs, interrompu). Les entrées déplacées sont
+/// indexées avant tout fsync ou écriture du journal: une erreur là ne les rend pas orphelines.
+fn bury_parallel(
+    idx: &mut index::Index,
+    slots: Vec<Slot<'_>>,
+    jobs: usize,
+) -> Result<(usize, bool)> {
+    let mut failed = 0;
+    let pending: Vec<String> = slots
+        .iter()
+        .map(|s| format!("PENDING\t{}\t{}", s.original.display(), s.dst.display()))
+        .collect();
+    journal::append_all(&pending)?;
+
+    // Ctrl-C pendant le lot: chaque fil finit (ou annule) son élément, aucun autre ne démarre
+    let _sigint = InterruptGuard::install();
+    crate::progress::hide();
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<Result<()>>> = slots.iter().map(|_| None).collect();
+    std::thread::scope(|scope| {
+        let (slots, next) = (&slots, &next);
+        let workers: Vec<_> = (0..jobs.min(slots.len()))
+            .map(|_| {
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+                    while check_interrupt().is_ok() {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(slot) = slots.get(i) else { break };
+                        let moved = match &slot.reservation {
+                            Some(r) => r.fill_deferred(slot.src),
+                            None => safe_move_deferred(slot.src, &slot.dst),
+                        };
+                        done.push((i, moved));
+                    }
+                    done
+                })
+            })
+            .collect();
+        for w in workers {
+            for (i, moved) in w.join().expect("bury worker panicked") {
+                results[i] = Some(moved);
+            }
+        }
+    });
+
+    let mut dirs: HashSet<PathBuf> = HashSet::new();
+    let mut lines = Vec::new();
+    let mut unsettled = Vec::new();
+    let mut cancelled = false;
+    for (slot, moved) in slots.into_iter().zip(results) {
+        match moved {
+            Some(Ok(())) => {
+                if let Some(d) = slot.dst.parent() {
+                    dirs.insert(d.to_path_buf());
+                }
+                lines.push(format!(
+                    "DONE\t{}\t{}",
+                    slot.original.display(),
+                    slot.dst.display()
+                ));
+                let entry = buried_entry(
+                    slot.original,
+                    slot.dst,
+                    slot.kind,
+                    slot.holder,
+                    slot.usage,
+                );
+                announce_buried(&entry);
+                idx.items.push(entry);
+            }
+            Some(Err(e)) if !is_cancelled(&e) => {
+                report_failure(slot.src, &e);
+                unsettled.push((slot.original, slot.dst));
+                failed += 1;
+            }
+            // Annulé en cours de copie, ou jamais commencé: source intacte
+            _ => {
+                if let Some(r) = slot.reservation {
+                    r.release();
+                }
+                lines.push(format!(
+                    "ABORTED\t{}\t{}",
+                    slot.original.display(),
+                    slot.dst.display()
+                ));
+                cancelled = true;
+            }
+        }
+    }
+
+    // Un fsync par dossier cible plutôt qu'un par élément
+    for d in &dirs {
+        fsync_dir(d)?;
+    }
+    journal::append_all(&lines)?;
+    for (original, dst) in unsettled {
+        journal::bury_failed(idx, &original, &dst)?;
+    }
+    Ok((failed, cancelled))
+}
+
+pub fn list(filter: &crate::filter::Filter, tree: bool) -> anyhow::Result<()> {
+    let entries = filter.apply(index::load_entries()?);
+    if output::is_machine() {
+        for e in &entries {
+            output::emit(serde_json::to_value(output::EntryRecord::new(e, display_id(e)))?);
+        }
+        return Ok(());
+    }
+    let now_secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if tree {
+        // Tailles enregistrées seulement: `--list` ne reparcourt pas le graveyard
+        for line in crate::tree::render(&entries, |e| e.size, &TreeLabels { now_secs }) {
+            say!("{line}");
+        }
+        return Ok(());
+    }
+    let cfg = &crate::config::get().list;
+    let template = Template::parse(&cfg.format)?;
     for e in entries {
-        let id = display_id(&e);
-        let base = index::basename_of_original(&e);
-        // horodatage local lisible
-        let dt = Local
-            .timestamp_opt(e.deleted_at, 0)
-            .single()
-            .unwrap_or_else(|| Local.timestamp_opt(0, 0).single().unwrap());
-        let absolute = dt.format("%Y-%m-%d %H:%M:%S").to_string();
-        // âge relatif compact
-        let now_secs = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let rel = compact_age(now_secs.saturating_sub(e.deleted_at as u64));
-        let k = kind_letter(e.kind);
-        let ico = kind_icon(e.kind);
-
-        // Couleurs sobres (respectées/neutralisées par yansi::enable/disable dans main.rs)
-        let id_p = Paint::new(format!("{id:7}")).dim();
-        let icon_p = Paint::new(ico).fg(Color::Cyan);
-        let k_p = Paint::new(k).fg(Color::Cyan);
-        let date_p = Paint::new(format!("({absolute})")).dim();
-        let name_p = Paint::new(base).bold();
-        let path_p = Paint::new(e.original_path.display()).dim();
-        let age_p = Paint::new(format!("({rel})")).italic().dim();
-
-        println!("{id_p}  {icon_p} {k_p}  {date_p}  {name_p}  {path_p}  {age_p}");
+        let value = |f: Field| match f {
+            Field::Id => display_id(&e),
+            Field::Icon => kind_icon(e.kind).to_string(),
+            Field::Kind => kind_letter(e.kind).to_string(),
+            // horodatage local lisible
+            Field::DeletedAt => Local
+                .timestamp_opt(e.deleted_at, 0)
+                .single()
+                .unwrap_or_else(|| Local.timestamp_opt(0, 0).single().unwrap())
+                .format(&cfg.time_format)
+                .to_string(),
+            // âge relatif compact
+            Field::Age => compact_age(now_secs.saturating_sub(e.deleted_at as u64)),
+            Field::Basename => index::basename_of_original(&e),
+            Field::OriginalPath => e.original_path.display().to_string(),
+            Field::TrashedPath => e.trashed_path.display().to_string(),
+            Field::Graveyard => e
+                .graveyard
+                .as_deref()
+                .map_or_else(|| "-".to_string(), |g| g.display().to_string()),
+            // "-" quand l'index n'a pas de mesure (anciens index, corbeille)
+            Field::Size => e.size.map_or_else(|| "-".to_string(), human_size),
+            Field::DiskUsage => e.disk_usage.map_or_else(|| "-".to_string(), human_size),
+        };
+        say!("{}", template.render(value, paint_field));
     }
     Ok(())
 }
 
+/// Lignes de `--tree`: entrée comme dans `list`, dossier avec son bilan
+struct TreeLabels {
+    now_secs: u64,
+}
+
+impl crate::tree::Labels for TreeLabels {
+    fn entry(&self, name: &str, e: &Entry, size: Option<u64>) -> String {
+        let rel = compact_age(self.now_secs.saturating_sub(e.deleted_at as u64));
+        format!(
+            "{} {}  {}  {}  {}",
+            Paint::new(kind_icon(e.kind)).fg(Color::Cyan),
+            Paint::new(name).bold(),
+            Paint::new(size.map_or_else(|| "-".to_string(), human_size)).fg(Color::Yellow),
+            Paint::new(display_id(e)).dim(),
+            Paint::new(format!("({rel})")).italic().dim()
+        )
+    }
+
+    fn dir(&self, name: &str, count: usize, size: u64) -> String {
+        format!(
+            "{}  {}",
+            Paint::new(name).fg(Color::Blue).bold(),
+            Paint::new(format!("({count} item(s), {})", human_size(size))).dim()
+        )
+    }
+}
+
+/// Couleurs sobres par champ (respectées/neutralisées par yansi::enable/disable dans main.rs)
+fn paint_field(f: Field, s: String) -> String {
+    let style = match f {
+        Field::Id | Field::DeletedAt | Field::OriginalPath | Field::TrashedPath | Field::Graveyard => {
+            Style::new().dim()
+        }
+        Field::Icon | Field::Kind => Style::new().fg(Color::Cyan),
+        Field::Size | Field::DiskUsage => Style::new().fg(Color::Yellow),
+        Field::Basename => Style::new().bold(),
+        Field::Age => Style::new().italic().dim(),
+    };
+    s.paint(style).to_string()
+}
+
+/// Supprime un élément du graveyard (fichier/dir); absent = déjà supprimé
+fn remove_trashed(p: &path::Path) -> io::Result<()> {
+    let res = if p.is_dir() {
+        fs::remove_dir_all(p)
+    } else {
+        fs::remove_file(p).or_else(|err| {
+            if err.kind() == io::ErrorKind::IsADirectory {
+                fs::remove_dir_all(p)
+            } else {
+                Err(err)
+            }
+        })
+    };
+    match res {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+/// Retire `to_delete` du graveyard et de l'index en une seule transaction.
+/// `sweep`: la sélection couvrait tout → on nettoie aussi les résidus.
+/// `action`: nom publié pour chaque entrée retirée (`--json`).
+fn cremate_committed(to_delete: &[Entry], sweep: bool, action: &str) -> Result<usize> {
+    let set: HashSet<PathBuf> = to_delete.iter().map(|e| e.trashed_path.clone()).collect();
+
+    index::with_index_mut(|idx| {
+        // Revalide la sélection côté index courant (au cas où ça a bougé)
+        let mut remaining: Vec<Entry> = Vec::with_capacity(idx.items.len());
+        let mut removed_count = 0usize;
+
+        for e in idx.items.drain(..) {
+            if set.contains(&e.trashed_path) {
+                // Supprimer la cible (fichier/dir)
+                if let Err(err) = remove_trashed(&e.trashed_path) {
+                    eprintln!("warn: cannot remove {}: {}", e.trashed_path.display(), err);
+                    output::failed(&e.trashed_path, &err);
+                    // Échec : on conserve l’entrée
+                    remaining.push(e);
+                    continue;
+                }
+                // Succès → on ne remet pas l'entrée (deleted)
+                output::done(action, &e, display_id(&e));
+                removed_count += 1;
+            } else {
+                remaining.push(e);
+            }
+        }
+
+        // Si la sélection couvrait "tout", on peut en plus nettoyer les résidus
+        // des graveyards sans toucher aux méta (.journal/.index.lock)
+        // (pas en mode FreeDesktop: la corbeille est partagée avec d'autres outils)
+        if sweep && crate::config::get().storage == Storage::Graveyard {
+            let mut gys: Vec<PathBuf> = vec![graveyard_dir()?];
+            gys.extend(to_delete.iter().filter_map(|e| e.graveyard.clone()));
+            gys.sort();
+            gys.dedup();
+            for gy in &gys {
+                let Ok(rd) = fs::read_dir(gy) else { continue };
+                for ent in rd.filter_map(|r| r.ok()) {
+                    let p = ent.path();
+                    let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
+                    if matches!(name, ".journal" | ".journal.1" | ".index.lock") {
+                        continue;
+                    }
+                    let _ = if p.is_dir() {
+                        fs::remove_dir_all(&p)
+                    } else {
+                        fs::remove_file(&p)
+                    };
+                }
+            }
+        }
+
+        idx.items = remaining;
+        Ok(removed_count)
+    })
+}
+
+/// Bilan d'occupation d'une sélection: "1.2 MiB, 1.3 MiB on disk"
+fn selection_usage(entries: &[Entry]) -> String {
+    let mut total = Usage::default();
+    for e in entries {
+        total += crate::size::entry_usage(e);
+    }
+    format!(
+        "{}, {} on disk",
+        human_size(total.apparent),
+        human_size(total.disk)
+    )
+}
+
 /// `cremate` sans cible = vider tout ; avec cible = supprimer les matches.
 pub fn cremate(target: Option<String>, dry_run: bool, yes: bool) -> anyhow::Result<()> {
     // --- 1) SNAPSHOT & SÉLECTION (hors verrou) ---
     let snap = index::load_index()?; // snapshot
     if snap.items.is_empty() {
-        println!("Graveyard is empty.");
+        say!("Graveyard is empty.");
         return Ok(());
     }
 
     // Construire la sélection "to_delete" depuis le snapshot
     let to_delete: Vec<index::Entry> = if let Some(ref q0) = target {
-        let q = q0.to_lowercase();
-        let matches: Vec<index::Entry> = snap
-            .items
-            .iter()
-            .filter(|&e| {
-                let base = index::basename_of_original(e).to_lowercase();
-                let id = display_id(e).to_lowercase();
-                base.contains(&q) || id.starts_with(&q)
-            })
-            .cloned()
-            .collect();
+        let matches = match_target(&snap.items, q0);
         if matches.is_empty() {
-            println!("No graveyard entry matches '{q0}'.");
+            say!("No graveyard entry matches '{q0}'.");
             return Ok(());
         }
         if matches.len() > 1 && !yes {
-            println!("Multiple matches (use TAB completion or add -y to cremate all of them):");
+            say!("Multiple matches (use TAB completion or add -y to cremate all of them):");
             for m in &matches {
                 let id = display_id(m);
-                println!("  {:7}  {}", id, index::basename_of_original(m));
+                say!("  {:7}  {}", id, index::basename_of_original(m));
             }
             return Ok(());
         }
@@ -432,7 +1475,7 @@ pub fn cremate(target: Option<String>, dry_run: bool, yes: bool) -> anyhow::Resu
         // Interactif (fzf) sur le snapshot
         let picks = crate::ui::pick_entries_with_fzf(&snap, /*preview=*/ false)?;
         if picks.is_empty() {
-            println!("Aborted.");
+            say!("Aborted.");
             return Ok(());
         }
         picks.into_iter().map(|i| snap.items[i].clone()).collect()
@@ -440,127 +1483,134 @@ pub fn cremate(target: Option<String>, dry_run: bool, yes: bool) -> anyhow::Resu
 
     // Rien ?
     if to_delete.is_empty() {
-        println!("Nothing to delete.");
+        say!("Nothing to delete.");
         return Ok(());
     }
 
     // Bilan (hors verrou)
-    let mut total_bytes: u64 = 0;
-    for e in &to_delete {
-        if let Ok(meta) = fs::metadata(&e.trashed_path) {
-            total_bytes = total_bytes.saturating_add(meta.len());
-        }
-    }
-    let mb = (total_bytes as f64) / (1024.0 * 1024.0);
+    let usage = selection_usage(&to_delete);
 
     let is_all = to_delete.len() == snap.items.len();
     if is_all {
-        println!(
-            "About to remove ALL graveyard items: {} items (~{:.2} MiB)",
+        say!(
+            "About to remove ALL graveyard items: {} items ({})",
             to_delete.len(),
-            mb
+            usage
         );
         if dry_run {
-            println!("--dry-run: nothing deleted.");
+            say!("--dry-run: nothing deleted.");
             return Ok(());
         }
-        if !yes {
-            print!("Type YES to confirm: ");
-            io::stdout().flush()?;
-            let mut buf = String::new();
-            io::stdin().read_line(&mut buf)?;
-            if buf.trim() != "YES" {
-                println!("Aborted.");
-                return Ok(());
-            }
+        let confirmed = yes
+            || match crate::config::get().confirm.cremate_all {
+                CremateAllConfirm::TypeYes => confirm_type_yes()?,
+                CremateAllConfirm::YesNo => confirm_yes_no()?,
+                CremateAllConfirm::Never => true,
+            };
+        if !confirmed {
+            say!("Aborted.");
+            return Ok(());
         }
     } else {
-        println!(
-            "About to remove {} item(s) (~{:.2} MiB).",
-            to_delete.len(),
-            mb
-        );
+        say!("About to remove {} item(s) ({}).", to_delete.len(), usage);
         if dry_run {
-            println!("--dry-run: nothing deleted.");
+            say!("--dry-run: nothing deleted.");
             return Ok(());
         }
-        if !yes && to_delete.len() == 1 {
-            print!("Confirm (y/N): ");
-            io::stdout().flush()?;
-            let mut buf = String::new();
-            io::stdin().read_line(&mut buf)?;
-            if buf.trim().to_lowercase() != "y" {
-                println!("Aborted.");
-                return Ok(());
-            }
+        if !yes && to_delete.len() == 1 && !confirm_yes_no()? {
+            say!("Aborted.");
+            return Ok(());
         }
     }
 
     // --- 2) COMMIT ATOMIQUE (sous verrou unique) ---
-    let set: HashSet<PathBuf> = to_delete.iter().map(|e| e.trashed_path.clone()).collect();
+    let removed = cremate_committed(&to_delete, is_all, "cremated")?;
 
-    let removed = index::with_index_mut(|idx| {
-        // Revalide la sélection côté index courant (au cas où ça a bougé)
-        let mut remaining: Vec<Entry> = Vec::with_capacity(idx.items.len());
-        let mut removed_count = 0usize;
+    say!("Removed {removed} item(s).");
+    Ok(())
+}
 
-        for e in idx.items.drain(..) {
-            if set.contains(&e.trashed_path) {
-                // Supprimer la cible (fichier/dir)
-                let p = &e.trashed_path;
-                let res = if p.is_dir() {
-                    fs::remove_dir_all(p)
-                } else {
-                    fs::remove_file(p).or_else(|err| {
-                        if err.kind() == std::io::ErrorKind::IsADirectory {
-                            fs::remove_dir_all(p)
-                        } else {
-                            Err(err)
-                        }
-                    })
-                };
-                match res {
-                    Ok(_) => removed_count += 1,
-                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => removed_count += 1,
-                    Err(err) => {
-                        eprintln!("warn: cannot remove {}: {}", p.display(), err);
-                        // Échec : on conserve l’entrée
-                        remaining.push(e);
-                        continue;
-                    }
-                }
-                // Succès → on ne remet pas l'entrée (deleted)
-            } else {
-                remaining.push(e);
-            }
-        }
+/// Élague les entrées enterrées depuis plus de `older_than` (défaut: `prune.retention`).
+pub fn prune(older_than: Option<Duration>, dry_run: bool, yes: bool) -> Result<()> {
+    let Some(age) = older_than.or_else(|| crate::config::get().prune.retention()) else {
+        anyhow::bail!("no retention: pass --older-than or set `prune.retention` in config.toml");
+    };
+    let cutoff = Utc::now().timestamp().saturating_sub(age.as_secs() as i64);
+    let age_h = humantime::format_duration(age);
 
-        // Si la sélection couvrait "tout", on peut en plus nettoyer les résidus
-        // du graveyard sans toucher aux méta (.journal/.index.lock)
-        if is_all {
-            let gy = crate::paths::data_dir()?.join("graveyard");
-            if let Ok(rd) = fs::read_dir(&gy) {
-                for ent in rd.filter_map(|r| r.ok()) {
-                    let p = ent.path();
-                    let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-                    if name == ".journal" || name == ".index.lock" {
-                        continue;
-                    }
-                    let _ = if p.is_dir() {
-                        fs::remove_dir_all(&p)
-                    } else {
-                        fs::remove_file(&p)
-                    };
-                }
-            }
-            remaining.resetting();
-        }
+    let snap = index::load_index()?;
+    let expired: Vec<Entry> = snap
+        .items
+        .into_iter()
+        .filter(|e| e.deleted_at < cutoff)
+        .collect();
+    if expired.is_empty() {
+        say!("Nothing buried for more than {age_h}.");
+        return Ok(());
+    }
 
-        idx.items = remaining;
-        Ok(removed_count)
-    })?;
+    say!(
+        "About to prune {} item(s) buried for more than {} ({}):",
+        expired.len(),
+        age_h,
+        selection_usage(&expired)
+    );
+    let now = Utc::now().timestamp();
+    for e in &expired {
+        let rel = compact_age(now.saturating_sub(e.deleted_at) as u64);
+        say!(
+            "  {:7}  {}  {}",
+            display_id(e),
+            Paint::new(index::basename_of_original(e)).bold(),
+            Paint::new(format!("({rel})")).italic().dim()
+        );
+    }
+    if dry_run {
+        say!("--dry-run: nothing deleted.");
+        return Ok(());
+    }
+    if !yes && !confirm_yes_no()? {
+        say!("Aborted.");
+        return Ok(());
+    }
+
+    let removed = cremate_committed(&expired, false, "pruned")?;
+    say!("Pruned {removed} item(s).");
+    Ok(())
+}
+
+/// Élagage automatique après `bury` (`prune.auto = true`): sans invite, discret
+pub fn auto_prune() -> Result<()> {
+    let Some(age) = crate::config::get().prune.retention() else {
+        return Ok(());
+    };
+    let cutoff = Utc::now().timestamp().saturating_sub(age.as_secs() as i64);
+    let expired: Vec<Entry> = index::load_entries()?
+        .into_iter()
+        .filter(|e| e.deleted_at < cutoff)
+        .collect();
+    if expired.is_empty() {
+        return Ok(());
+    }
+    let removed = cremate_committed(&expired, false, "pruned")?;
+    say!(
+        "{} {}",
+        Paint::new("🔥").fg(Color::Red),
+        Paint::new(format!(
+            "pruned {removed} item(s) older than {}",
+            humantime::format_duration(age)
+        ))
+        .dim()
+    );
+    Ok(())
+}
 
-    println!("Removed {removed} item(s).");
+/// `--recover`: rejoue le journal et répare l'index après un arrêt brutal
+pub fn recover_cmd() -> Result<()> {
+    match index::recover()? {
+        0 => say!("Nothing to recover."),
+        n => say!("Recovered {n} interrupted operation(s)."),
+    }
     Ok(())
 }
 
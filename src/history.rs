@@ -0,0 +1,254 @@
+//! Versions d'un chemin enterré plusieurs fois (sauvegardes d'éditeur, configs régénérées).
+//! Génération 1 = la dernière enterrée, 2 = celle d'avant…; 0 = le chemin vivant, s'il existe.
+
+use anyhow::{Context, Result, bail};
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::graveyard::{compact_age, display_id, match_target};
+use crate::index::{self, Entry};
+use crate::output::{self, EntryRecord, say};
+use crate::size::human_size;
+
+/// Quelle version d'un chemin (`--generation`, `--as-of`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    /// N-ième version précédente, 1 = la plus récente
+    Nth(usize),
+    /// Celle qui était en place à cet instant (secondes Unix)
+    AsOf(i64),
+}
+
+/// `--as-of`: durée écoulée (`2h`, `3d`), RFC 3339, ou date locale `YYYY-MM-DD[ HH:MM[:SS]]`
+pub fn parse_as_of(s: &str) -> Result<i64> {
+    let s = s.trim();
+    if let Ok(d) = humantime::parse_duration(s) {
+        return Ok(Utc::now().timestamp() - d.as_secs() as i64);
+    }
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Ok(t.timestamp());
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        });
+    match naive.and_then(|n| Local.from_local_datetime(&n).earliest()) {
+        Some(t) => Ok(t.timestamp()),
+        None => bail!("expected a duration (2h, 3d), RFC 3339 or YYYY-MM-DD[ HH:MM[:SS]], got '{s}'"),
+    }
+}
+
+/// Générations de `original`, la plus récente d'abord
+pub fn generations(entries: &[Entry], original: &Path) -> Vec<Entry> {
+    let mut out: Vec<Entry> = entries
+        .iter()
+        .filter(|e| e.original_path == original)
+        .cloned()
+        .collect();
+    // Tri stable: à seconde égale, l'ordre d'enterrement (celui de l'index) départage
+    out.sort_by_key(|e| e.deleted_at);
+    out.reverse();
+    out
+}
+
+/// La version voulue parmi `versions` (la plus récente d'abord)
+pub fn select(versions: &[Entry], v: Version) -> Result<&Entry> {
+    let path = versions
+        .first()
+        .map(|e| e.original_path.display().to_string())
+        .unwrap_or_default();
+    match v {
+        Version::Nth(0) => bail!("--generation starts at 1 (the latest buried version)"),
+        Version::Nth(n) => versions.get(n - 1).with_context(|| {
+            format!("{path} has {} buried version(s), not {n}", versions.len())
+        }),
+        // En place à `t`: la première enterrée après `t`
+        Version::AsOf(t) => match versions.iter().rev().find(|e| e.deleted_at > t) {
+            Some(e) => Ok(e),
+            None => bail!(
+                "nothing of {path} was buried after {}; the version as of then is the current one",
+                when(t)
+            ),
+        },
+    }
+}
+
+/// Resserre `matches` à une version par chemin. Avec `version` (un seul chemin), la version
+/// demandée; sans, la plus récente de chaque chemin (sauf `all`: chaque version est gardée).
+pub fn narrow(matches: Vec<Entry>, version: Option<Version>, all: bool) -> Result<Vec<Entry>> {
+    let Some(first) = matches.first() else {
+        return Ok(matches);
+    };
+    let one_path = matches.iter().all(|e| e.original_path == first.original_path);
+    match version {
+        Some(v) if one_path => {
+            let versions = generations(&matches, &first.original_path);
+            Ok(vec![select(&versions, v)?.clone()])
+        }
+        Some(_) => {
+            let mut paths: Vec<&Path> = matches.iter().map(|e| e.original_path.as_path()).collect();
+            paths.sort();
+            paths.dedup();
+            eprintln!("Paths matching:");
+            for p in &paths {
+                eprintln!("  {}", p.display());
+            }
+            bail!("--generation/--as-of need a single path, got {}", paths.len())
+        }
+        None if all => Ok(matches),
+        None => {
+            let mut latest: Vec<Entry> = Vec::with_capacity(matches.len());
+            for e in &matches {
+                if latest.iter().any(|l| l.original_path == e.original_path) {
+                    continue;
+                }
+                let versions = generations(&matches, &e.original_path);
+                if versions.len() > 1 {
+                    eprintln!(
+                        "note: {} has {} buried versions; taking the latest (see --history)",
+                        e.original_path.display(),
+                        versions.len()
+                    );
+                }
+                latest.push(versions[0].clone());
+            }
+            Ok(latest)
+        }
+    }
+}
+
+/// Le chemin désigné par TARGET et ses générations
+fn resolve(entries: &[Entry], target: &str) -> Result<(PathBuf, Vec<Entry>)> {
+    let matches = match_target(entries, target);
+    let Some(first) = matches.first() else {
+        bail!("No graveyard entry matches '{target}'");
+    };
+    if matches.iter().any(|e| e.original_path != first.original_path) {
+        eprintln!("Multiple paths match:");
+        let mut paths: Vec<&Path> = matches.iter().map(|e| e.original_path.as_path()).collect();
+        paths.sort();
+        paths.dedup();
+        for p in paths {
+            eprintln!("  {}", p.display());
+        }
+        bail!("'{target}' matches several paths; give the full path");
+    }
+    let path = first.original_path.clone();
+    let versions = generations(entries, &path);
+    Ok((path, versions))
+}
+
+fn when(ts: i64) -> String {
+    let fmt = &crate::config::get().list.time_format;
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|d| d.format(fmt).to_string())
+        .unwrap_or_default()
+}
+
+/// `--history PATH`: toutes les générations, la plus récente d'abord
+pub fn history_cmd(target: &str) -> Result<()> {
+    let entries = index::load_entries()?;
+    let (path, versions) = resolve(&entries, target)?;
+
+    if output::is_machine() {
+        for (i, e) in versions.iter().enumerate() {
+            let mut v = serde_json::to_value(EntryRecord::new(e, display_id(e)))?;
+            v["generation"] = (i + 1).into();
+            output::emit(v);
+        }
+        return Ok(());
+    }
+
+    say!("{}: {} buried version(s), newest first", path.display(), versions.len());
+    if let Ok(md) = std::fs::symlink_metadata(&path) {
+        let mtime = md
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs() as i64);
+        say!(
+            "  @0  {:7}  {}  {:>10}  {:>9}",
+            "current",
+            when(mtime),
+            "",
+            human_size(crate::size::measure(&path).apparent)
+        );
+    }
+    let now = Utc::now().timestamp();
+    for (i, e) in versions.iter().enumerate() {
+        let age = compact_age(now.saturating_sub(e.deleted_at).max(0) as u64);
+        say!(
+            "  @{:<2} {:7}  {}  {:>10}  {:>9}",
+            i + 1,
+            display_id(e),
+            when(e.deleted_at),
+            format!("{age} ago"),
+            human_size(crate::size::entry_usage(e).apparent)
+        );
+    }
+    say!("Restore one with `nrip -r {} --generation N` (or --as-of TIME).", path.display());
+    Ok(())
+}
+
+/// `--diff A[:B]`: générations à comparer; B vaut 0 (le chemin vivant) par défaut
+pub fn parse_diff(s: &str) -> Result<(usize, usize)> {
+    let generation = |g: &str| -> Result<usize> {
+        g.trim()
+            .trim_start_matches('@')
+            .parse()
+            .map_err(|_| anyhow::anyhow!("expected GEN or GEN:GEN (e.g. 2 or 3:1), got '{s}'"))
+    };
+    match s.split_once(':') {
+        Some((a, b)) => Ok((generation(a)?, generation(b)?)),
+        None => Ok((generation(s)?, 0)),
+    }
+}
+
+/// `--history PATH --diff A:B`: `diff -u` (`-r` pour les dossiers) entre deux générations
+pub fn diff_cmd(target: &str, (a, b): (usize, usize)) -> Result<()> {
+    let entries = index::load_entries()?;
+    let (path, versions) = resolve(&entries, target)?;
+    let side = |g: usize| -> Result<(PathBuf, String)> {
+        if g == 0 {
+            if std::fs::symlink_metadata(&path).is_err() {
+                bail!("{} does not exist now; compare two generations (e.g. 2:1)", path.display());
+            }
+            return Ok((path.clone(), format!("{} (current)", path.display())));
+        }
+        let e = select(&versions, Version::Nth(g))?;
+        Ok((
+            e.trashed_path.clone(),
+            format!("{}@{g} (buried {})", path.display(), when(e.deleted_at)),
+        ))
+    };
+    let (old, old_label) = side(a)?;
+    let (new, new_label) = side(b)?;
+
+    let mut cmd = Command::new("diff");
+    if old.is_dir() || new.is_dir() {
+        cmd.arg("-r");
+    }
+    let status = cmd
+        .arg("-u")
+        .args(["--label", &old_label, "--label", &new_label])
+        .arg(&old)
+        .arg(&new)
+        .status()
+        .context("diff not found (please install `diff`)")?;
+    // 0: identiques, 1: différents, au-delà: erreur de diff
+    match status.code() {
+        Some(0) => {
+            eprintln!("@{a} and @{b} are identical.");
+            Ok(())
+        }
+        Some(1) => Ok(()),
+        _ => bail!("diff failed ({status})"),
+    }
+}
@@ -1,11 +1,10 @@
 use anyhow::{Context, Result};
-use fd_lock::RwLock;
+use fd_lock::{RwLock, RwLockWriteGuard};
 use fs_err as fs;
 use serde::{Deserialize, Serialize};
-use serde_json as json;
-use std::io::Write;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use tempfile::NamedTempFile;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum Kind {
@@ -16,13 +15,53 @@ pub enum Kind {
     Other,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl std::str::FromStr for Kind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "file" | "f" => Ok(Kind::File),
+            "dir" | "d" => Ok(Kind::Dir),
+            "symlink" | "l" => Ok(Kind::Symlink),
+            "other" => Ok(Kind::Other),
+            other => anyhow::bail!("expected file|dir|symlink|other, got '{other}'"),
+        }
+    }
+}
+
+impl Kind {
+    pub fn from_file_type(ft: std::fs::FileType) -> Kind {
+        if ft.is_dir() {
+            Kind::Dir
+        } else if ft.is_file() {
+            Kind::File
+        } else if ft.is_symlink() {
+            Kind::Symlink
+        } else {
+            Kind::Other
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Entry {
     pub original_path: PathBuf,
     pub trashed_path: PathBuf,
     pub deleted_at: i64,
-    #[serde(default)]
-    pub kind: Kind, // ← nouveau champ, défaut = Other pour compat avec anciens index
+    /// Obligatoire depuis le schéma v1 (les index v0 sont complétés par migration)
+    pub kind: Kind,
+    /// Graveyard de point de montage qui détient l'entrée (None = graveyard principal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graveyard: Option<PathBuf>,
+    /// Taille apparente récursive (octets), mesurée à l'enterrement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Espace disque alloué (st_blocks × 512), liens durs comptés une fois
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_usage: Option<u64>,
+    /// Champs inconnus de ce binaire, réécrits tels quels
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -30,65 +69,138 @@ pub struct Index {
     pub items: Vec<Entry>,
 }
 
-fn index_paths() -> Result<(PathBuf, PathBuf, PathBuf)> {
+/// Dossier de l'index (data dir); crée le graveyard au passage
+fn index_dir() -> Result<PathBuf> {
     let data_dir = crate::paths::data_dir()?;
-    let idx_dir = data_dir.clone();
-    let gy_dir = data_dir.join("graveyard");
-    let idx = idx_dir.join("index.json");
-    fs::create_dir_all(&gy_dir)?;
-    Ok((idx, idx_dir, gy_dir))
+    fs::create_dir_all(crate::paths::graveyard_dir()?)?;
+    Ok(data_dir)
+}
+
+fn is_freedesktop() -> bool {
+    crate::config::get().storage == crate::config::Storage::Freedesktop
 }
 
 fn lock_path(dir: &Path) -> PathBuf {
     dir.join(".index.lock")
 }
 
+/// Ouvert sans tronquer: le détenteur du verrou exclusif y a inscrit PID et commande
+fn open_lock(dir: &Path) -> Result<RwLock<fs::File>> {
+    let f = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path(dir))
+        .context("open lock file")?;
+    Ok(RwLock::new(f))
+}
+
+/// Détenteur d'après le fichier de verrou (vide: lecteur, ou nrip antérieur)
+fn holder(dir: &Path) -> String {
+    let text = std::fs::read_to_string(lock_path(dir)).unwrap_or_default();
+    let mut it = text.trim_end().splitn(3, '\t');
+    match (it.next(), it.next().and_then(|s| s.parse::<i64>().ok()), it.next()) {
+        (Some(pid), Some(since), Some(cmd)) if !pid.is_empty() => {
+            let held = Duration::from_secs((chrono::Utc::now().timestamp() - since).max(0) as u64);
+            format!(
+                "PID {pid} (`{cmd}`, for {})",
+                humantime::format_duration(held)
+            )
+        }
+        _ => "another nrip process".into(),
+    }
+}
+
+/// Après un essai raté: attendre encore (avec un avis après 1 s) ou abandonner
+/// selon `lock.timeout` (0 = `--no-wait`)
+fn wait_or_give_up(start: Instant, notified: &mut bool, dir: &Path) -> Result<()> {
+    let timeout = crate::config::get().lock.timeout();
+    if start.elapsed() >= timeout {
+        if timeout.is_zero() {
+            anyhow::bail!("index is locked by {}; not waiting", holder(dir));
+        }
+        anyhow::bail!(
+            "gave up after {} waiting for the index lock held by {}",
+            humantime::format_duration(timeout),
+            holder(dir)
+        );
+    }
+    if !*notified && start.elapsed() >= Duration::from_secs(1) {
+        eprintln!("waiting for the index lock held by {}...", holder(dir));
+        *notified = true;
+    }
+    std::thread::sleep(Duration::from_millis(50));
+    Ok(())
+}
+
+/// `lock!(lock.try_read(), dir)`: boucle d'essais non bloquants (une fonction ne pourrait
+/// pas rendre le garde emprunté d'une boucle)
+macro_rules! lock {
+    ($attempt:expr, $dir:expr) => {{
+        let (start, mut notified) = (Instant::now(), false);
+        loop {
+            match $attempt {
+                Ok(guard) => break guard,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    wait_or_give_up(start, &mut notified, $dir)?
+                }
+                Err(e) => return Err(anyhow::Error::from(e).context("lock index")),
+            }
+        }
+    }};
+}
+
+/// Verrou exclusif tenu; le fichier est vidé avant de le relâcher
+struct Exclusive<'a>(RwLockWriteGuard<'a, fs::File>);
+
+impl Exclusive<'_> {
+    /// PID, début et commande, pour ceux qui attendent
+    fn sign(mut guard: RwLockWriteGuard<'_, fs::File>) -> Result<Exclusive<'_>> {
+        let cmd = std::env::args().collect::<Vec<_>>().join(" ");
+        guard.set_len(0)?;
+        guard.seek(SeekFrom::Start(0))?;
+        writeln!(
+            guard,
+            "{}\t{}\t{cmd}",
+            std::process::id(),
+            chrono::Utc::now().timestamp()
+        )?;
+        Ok(Exclusive(guard))
+    }
+}
+
+impl Drop for Exclusive<'_> {
+    fn drop(&mut self) {
+        let _ = self.0.set_len(0);
+    }
+}
+
 pub fn load_index() -> Result<Index> {
-    let (idx, dir, _) = index_paths()?;
+    let dir = index_dir()?;
     fs::create_dir_all(&dir)?;
-    let lockf = fs::File::create(lock_path(&dir)).context("create lock file")?;
-    let lock = RwLock::new(lockf);
-    let _guard = lock.read().context("lock index for read")?;
-    if !idx.exists() {
-        return Ok(Index::default());
+    let lock = open_lock(&dir)?;
+    let _guard = lock!(lock.try_read(), &dir);
+    if is_freedesktop() {
+        return Ok(Index {
+            items: crate::freedesktop::scan()?,
+        });
     }
-    let data = fs::read(&idx).with_context(|| format!("read {}", idx.display()))?;
-    let index: Index =
-        json::from_slice(&data).with_context(|| format!("parse {}", idx.display()))?;
-    Ok(index)
+    crate::store::open(&dir).load()
 }
 
 // API legacy, optionnelle
 #[cfg(feature = "legacy_api")]
 #[allow(dead_code)]
 pub fn save_index(idx: &Index) -> Result<()> {
-    let (idx_path, dir, _) = index_paths()?;
+    let dir = index_dir()?;
     fs::create_dir_all(&dir)?;
-    let lockf = fs::File::create(lock_path(&dir)).context("create lock file")?;
-    let mut lock = RwLock::new(lockf);
-    let _guard = lock.write().context("lock index for write")?;
-
-    let mut tmp = NamedTempFile::new_in(&dir).context("mkstemp in index dir")?;
-    let buf = serde_json::to_vec_pretty(idx).context("serialize index")?;
-    tmp.write_all(&buf).context("write tmp")?;
-    tmp.as_file().sync_all().context("fsync tmp")?;
-    let tmp_path = tmp.into_temp_path();
-    tmp_path.persist(&idx_path).map_err(|e| {
-        anyhow::anyhow!(
-            "rename {} -> {}: {}",
-            e.path.display(), // <- anciennement e.file.path().display()
-            idx_path.display(),
-            e.error
-        )
-    })?;
-
-    #[cfg(unix)]
-    {
-        use rustix::fs::{Mode, OFlags, open};
-        let df = open(&dir, OFlags::RDONLY | OFlags::DIRECTORY, Mode::empty())?;
-        rustix::fs::fdatasync(&df)?; // fdatasync
-    }
-    Ok(())
+    let mut lock = open_lock(&dir)?;
+    let _guard = Exclusive::sign(lock!(lock.try_write(), &dir))?;
+
+    let mut store = crate::store::open_migrated(&dir)?;
+    let before = store.load()?;
+    store.save(&before, idx)
 }
 
 /* ——— Shims de compat pour ton code actuel ——— */
@@ -117,45 +229,49 @@ pub fn with_index_mut<F, T>(mut f: F) -> Result<T>
 where
     F: FnMut(&mut Index) -> Result<T>,
 {
-    let (idx_path, dir, _) = index_paths()?;
+    transaction(|idx, _| f(idx))
+}
+
+/// Passe de récupération seule (`--recover`): nombre d'opérations réparées
+pub fn recover() -> Result<usize> {
+    transaction(|_, fixed| Ok(fixed))
+}
+
+/// Verrou → lecture → rejeu du journal → mutation → écriture atomique → `COMMIT`
+fn transaction<F, T>(mut f: F) -> Result<T>
+where
+    F: FnMut(&mut Index, usize) -> Result<T>,
+{
+    let dir = index_dir()?;
     fs::create_dir_all(&dir)?;
 
     // Un seul lock pour la durée de vie de la transaction
-    let lockf = fs::File::create(lock_path(&dir)).context("create lock file")?;
-    let mut lock = RwLock::new(lockf);
-    let _guard = lock.write().context("lock index for update")?;
-
-    // read (si existe)
-    let mut idx = if idx_path.exists() {
-        let data = fs::read(&idx_path).with_context(|| format!("read {}", idx_path.display()))?;
-        json::from_slice(&data).with_context(|| format!("parse {}", idx_path.display()))?
-    } else {
-        Index::default()
-    };
-
-    // user mutation
-    let out = f(&mut idx)?;
-
-    // write atomique
-    let mut tmp = NamedTempFile::new_in(&dir).context("mkstemp in index dir")?;
-    let buf = serde_json::to_vec_pretty(&idx).context("serialize index")?;
-    tmp.write_all(&buf).context("write tmp")?;
-    tmp.as_file().sync_all().context("fsync tmp")?;
-    let tmp_path = tmp.into_temp_path();
-    tmp_path.persist(&idx_path).map_err(|e| {
-        anyhow::anyhow!(
-            "rename {} -> {}: {}",
-            e.path.display(),
-            idx_path.display(),
-            e.error
-        )
-    })?;
-
-    #[cfg(unix)]
-    {
-        use rustix::fs::{Mode, OFlags, open};
-        let df = open(&dir, OFlags::RDONLY | OFlags::DIRECTORY, Mode::empty())?;
-        rustix::fs::fdatasync(&df)?;
+    let mut lock = open_lock(&dir)?;
+    let _guard = Exclusive::sign(lock!(lock.try_write(), &dir))?;
+
+    // Corbeille FreeDesktop: l'« index » est la liste des .trashinfo
+    if is_freedesktop() {
+        let before = crate::freedesktop::scan()?;
+        let mut idx = Index {
+            items: before.clone(),
+        };
+        let fixed = crate::journal::recover(&mut idx)?;
+        let out = f(&mut idx, fixed)?;
+        crate::freedesktop::commit(&before, &idx.items)?;
+        crate::journal::commit()?;
+        return Ok(out);
     }
+
+    // read (index.json, ou index.log selon `index.backend`)
+    let mut store = crate::store::open_migrated(&dir)?;
+    let before = store.load()?;
+    let mut idx = before.clone();
+
+    // opérations interrompues par un arrêt brutal, puis mutation
+    let fixed = crate::journal::recover(&mut idx)?;
+    let out = f(&mut idx, fixed)?;
+
+    store.save(&before, &idx)?;
+    crate::journal::commit()?;
     Ok(out)
 }
@@ -0,0 +1,350 @@
+//! `.journal` du graveyard: une ligne par étape, rejouée au démarrage de chaque
+//! transaction pour réparer ce qu'un arrêt brutal a laissé en plan.
+//!
+//! `PENDING|DONE <original> <trashed>`, `RESTORE_PENDING|RESTORE_DONE <trashed> <original>`
+//! (`<trashed>` peut être un chemin sous un dossier enterré), `EVICT <trashed>` (quota),
+//! `RECOVERED <action> <path>`,
+//! `ABORTED|RESTORE_ABORTED` (Ctrl-C pendant la copie, source intacte),
+//! `FAILED|RESTORE_FAILED <…> <…>` (échec réglé sur-le-champ), et `COMMIT` une fois
+//! l'index écrit. Passé `ROTATE_AT`, un journal clos par `COMMIT` devient `.journal.1`.
+
+use anyhow::Result;
+use chrono::Utc;
+use fs_err as fs;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::Storage;
+use crate::fs_safemove::{copying_path, parse_unique_name};
+use crate::index::{Entry, Index, Kind};
+
+/// Taille au-delà de laquelle le journal clos est mis de côté
+const ROTATE_AT: u64 = 256 * 1024;
+
+pub fn path() -> Result<PathBuf> {
+    Ok(crate::paths::graveyard_dir()?.join(".journal"))
+}
+
+/// Journal précédent, gardé pour `--doctor` (origine des orphelins)
+pub fn rotated_path() -> Result<PathBuf> {
+    Ok(crate::paths::graveyard_dir()?.join(".journal.1"))
+}
+
+pub fn append(line: &str) -> Result<()> {
+    append_all(&[line])
+}
+
+/// Plusieurs lignes, un seul fsync (enterrements par lots)
+pub fn append_all<S: AsRef<str>>(lines: &[S]) -> Result<()> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let jp = path()?;
+    let mut f = fs::OpenOptions::new().create(true).append(true).open(&jp)?;
+    let mut buf = String::new();
+    for l in lines {
+        buf.push_str(l.as_ref());
+        buf.push('\n');
+    }
+    f.write_all(buf.as_bytes())?;
+    f.sync_all()?;
+    Ok(())
+}
+
+/// Clôt la transaction courante (index écrit); rien n'est alors ouvert, le journal
+/// peut être mis de côté s'il a trop grossi
+pub fn commit() -> Result<()> {
+    append("COMMIT")?;
+    let jp = path()?;
+    if fs::metadata(&jp)?.len() < ROTATE_AT {
+        return Ok(());
+    }
+    fs::rename(&jp, rotated_path()?)?;
+    crate::fs_safemove::fsync_dir(jp.parent().unwrap_or(Path::new(".")))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Record {
+    /// Enterrement: chemin d'origine → chemin dans le graveyard
+    Bury { original: PathBuf, trashed: PathBuf },
+    /// Restauration: chemin dans le graveyard → chemin d'origine
+    Restore { trashed: PathBuf, original: PathBuf },
+    /// Éviction par le quota: l'entrée est supprimée du graveyard puis de l'index
+    Evict { trashed: PathBuf },
+    /// Index écrit: tout ce qui précède est clos
+    Commit,
+}
+
+/// Lecture tolérante: lignes inconnues ou incomplètes ignorées.
+/// Les anciens `PENDING <original> <basename>` (sans chemin cible) sont ignorés aussi.
+pub fn parse(text: &str) -> Vec<Record> {
+    let mut out = Vec::new();
+    for line in text.lines() {
+        let mut it = line.split('\t');
+        let (op, a, b) = (it.next(), it.next(), it.next());
+        let rec = match (op, a, b) {
+            (Some("COMMIT"), _, _) => Record::Commit,
+            (Some("PENDING" | "DONE"), Some(a), Some(b)) if Path::new(b).is_absolute() => {
+                Record::Bury {
+                    original: a.into(),
+                    trashed: b.into(),
+                }
+            }
+            (Some("RESTORE_PENDING" | "RESTORE_DONE"), Some(a), Some(b)) => Record::Restore {
+                trashed: a.into(),
+                original: b.into(),
+            },
+            (Some("EVICT"), Some(a), _) => Record::Evict { trashed: a.into() },
+            _ => continue,
+        };
+        out.push(rec);
+    }
+    out
+}
+
+fn read(p: &Path) -> Result<Vec<Record>> {
+    match fs::read_to_string(p) {
+        Ok(t) => Ok(parse(&t)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Tout l'historique connu: journal précédent puis courant (absents = vides)
+pub fn read_all() -> Result<Vec<Record>> {
+    let mut recs = read(&rotated_path()?)?;
+    recs.extend(read(&path()?)?);
+    Ok(recs)
+}
+
+/// Opérations non closes: tout ce qui suit le dernier `COMMIT`, sans doublons.
+/// Seul le journal courant compte: le précédent se termine par un `COMMIT`.
+pub fn open_records() -> Result<Vec<Record>> {
+    let recs = read(&path()?)?;
+    let start = recs
+        .iter()
+        .rposition(|r| *r == Record::Commit)
+        .map_or(0, |i| i + 1);
+    let mut seen = HashSet::new();
+    Ok(recs[start..]
+        .iter()
+        .filter(|r| seen.insert((*r).clone()))
+        .cloned()
+        .collect())
+}
+
+/// Réconcilie l'index avec le disque pour chaque opération interrompue.
+/// Ne touche jamais aux chemins de l'utilisateur: au pire on retire une copie
+/// partielle `.copying`. Retourne le nombre de réparations effectuées.
+pub fn recover(idx: &mut Index) -> Result<usize> {
+    let mut fixed = 0;
+    for rec in open_records()? {
+        fixed += settle(idx, rec)?;
+    }
+    Ok(fixed)
+}
+
+/// Enterrement qui a échoué autrement que par Ctrl-C: `FAILED`, puis réglé aussitôt comme
+/// après un arrêt brutal. Le `COMMIT` qui suit ne laisse donc pas sans entrée une cible
+/// déjà créée (copie faite, source non retirée).
+pub fn bury_failed(idx: &mut Index, original: &Path, trashed: &Path) -> Result<usize> {
+    append(&format!(
+        "FAILED\t{}\t{}",
+        original.display(),
+        trashed.display()
+    ))?;
+    settle(
+        idx,
+        Record::Bury {
+            original: original.into(),
+            trashed: trashed.into(),
+        },
+    )
+}
+
+/// Restauration qui a échoué autrement que par Ctrl-C: `RESTORE_FAILED`, puis réglée aussitôt
+pub fn restore_failed(idx: &mut Index, trashed: &Path, original: &Path) -> Result<usize> {
+    append(&format!(
+        "RESTORE_FAILED\t{}\t{}",
+        trashed.display(),
+        original.display()
+    ))?;
+    settle(
+        idx,
+        Record::Restore {
+            trashed: trashed.into(),
+            original: original.into(),
+        },
+    )
+}
+
+/// Règle une opération non close d'après le disque; 1 si quelque chose a été réparé
+fn settle(idx: &mut Index, rec: Record) -> Result<usize> {
+    match rec {
+        Record::Bury { original, trashed } => {
+            if idx.items.iter().any(|e| e.trashed_path == trashed) {
+                return Ok(0);
+            }
+            if let Ok(md) = std::fs::symlink_metadata(&trashed) {
+                // Déplacé mais jamais indexé: on l'adopte
+                let usage = crate::size::measure(&trashed);
+                let name = trashed.file_name().unwrap_or_default().to_string_lossy();
+                let deleted_at = parse_unique_name(&name)
+                    .map(|(ts, _)| ts)
+                    .unwrap_or_else(|| Utc::now().timestamp());
+                idx.items.push(Entry {
+                    original_path: original.clone(),
+                    trashed_path: trashed.clone(),
+                    deleted_at,
+                    kind: Kind::from_file_type(md.file_type()),
+                    graveyard: holder_of(&trashed),
+                    size: Some(usage.apparent),
+                    disk_usage: Some(usage.disk),
+                    extra: Default::default(),
+                });
+                note("ADOPTED", &trashed)?;
+                eprintln!(
+                    "recovered: {} was buried but not indexed; it is back in the list",
+                    original.display()
+                );
+                return Ok(1);
+            }
+            if discard_partial(&trashed)? | release_reservation(&trashed)? {
+                // Déplacement jamais abouti: la source est restée en place
+                note("ROLLED_BACK", &original)?;
+                eprintln!(
+                    "recovered: interrupted burial of {} rolled back",
+                    original.display()
+                );
+                return Ok(1);
+            }
+        }
+        Record::Restore { trashed, original } => {
+            let Some(pos) = idx.items.iter().position(|e| e.trashed_path == trashed) else {
+                return recover_extract(idx, &trashed, &original);
+            };
+            let in_graveyard = std::fs::symlink_metadata(&trashed).is_ok();
+            let restored = std::fs::symlink_metadata(&original).is_ok();
+            if !in_graveyard && restored {
+                // Restauré mais l'index n'a pas suivi
+                idx.items.remove(pos);
+                note("RESTORED", &original)?;
+                eprintln!("recovered: {} was already restored", original.display());
+                return Ok(1);
+            }
+            if in_graveyard && discard_partial(&original)? {
+                note("ROLLED_BACK", &trashed)?;
+                eprintln!(
+                    "recovered: interrupted restore of {} rolled back",
+                    original.display()
+                );
+                return Ok(1);
+            }
+            if in_graveyard && restored {
+                eprintln!(
+                    "warn: {} exists both in the graveyard and at its original place; left untouched",
+                    original.display()
+                );
+            }
+        }
+        Record::Evict { trashed } => {
+            let Some(pos) = idx.items.iter().position(|e| e.trashed_path == trashed) else {
+                return Ok(0);
+            };
+            // Suppression commencée: on l'achève, puis l'index suit
+            if std::fs::symlink_metadata(&trashed).is_ok()
+                && let Err(err) = crate::fs_safemove::remove_recursively(&trashed)
+            {
+                eprintln!("warn: cannot finish evicting {}: {err:#}", trashed.display());
+                return Ok(0);
+            }
+            let e = idx.items.remove(pos);
+            note("EVICTED", &trashed)?;
+            eprintln!(
+                "recovered: interrupted eviction of {} completed",
+                e.original_path.display()
+            );
+            return Ok(1);
+        }
+        Record::Commit => {}
+    }
+    Ok(0)
+}
+
+/// Chemin sorti d'un dossier enterré (`nrip -r dossier/chemin`): le dossier reste
+/// indexé, seule sa taille est à refaire, ou la copie partielle à retirer
+fn recover_extract(idx: &mut Index, inner: &Path, original: &Path) -> Result<usize> {
+    let Some(e) = idx
+        .items
+        .iter_mut()
+        .find(|e| inner != e.trashed_path && inner.starts_with(&e.trashed_path))
+    else {
+        return Ok(0);
+    };
+    let in_graveyard = std::fs::symlink_metadata(inner).is_ok();
+    if !in_graveyard && std::fs::symlink_metadata(original).is_ok() {
+        let usage = crate::size::measure(&e.trashed_path);
+        if e.size == Some(usage.apparent) && e.disk_usage == Some(usage.disk) {
+            return Ok(0);
+        }
+        e.size = Some(usage.apparent);
+        e.disk_usage = Some(usage.disk);
+        note("EXTRACTED", original)?;
+        eprintln!("recovered: {} was already restored", original.display());
+        return Ok(1);
+    }
+    if in_graveyard && discard_partial(original)? {
+        note("ROLLED_BACK", inner)?;
+        eprintln!(
+            "recovered: interrupted restore of {} rolled back",
+            original.display()
+        );
+        return Ok(1);
+    }
+    Ok(0)
+}
+
+fn note(action: &str, p: &Path) -> Result<()> {
+    append(&format!("RECOVERED\t{action}\t{}", p.display()))
+}
+
+/// Graveyard (ou corbeille) de montage qui détient `trashed`, None = principal
+pub fn holder_of(trashed: &Path) -> Option<PathBuf> {
+    match crate::config::get().storage {
+        Storage::Graveyard => {
+            let gy = trashed.parent()?;
+            let home = crate::paths::graveyard_dir().ok()?;
+            (gy != home).then(|| gy.to_path_buf())
+        }
+        Storage::Freedesktop => {
+            let trash = trashed.parent()?.parent()?;
+            let home = crate::freedesktop::home_trash().ok()?;
+            (trash != home).then(|| trash.to_path_buf())
+        }
+    }
+}
+
+/// Retire la copie partielle `<dst>.copying`; `true` si elle existait
+fn discard_partial(dst: &Path) -> Result<bool> {
+    let tmp = copying_path(dst);
+    if std::fs::symlink_metadata(&tmp).is_err() {
+        return Ok(false);
+    }
+    crate::fs_safemove::remove_recursively(&tmp)?;
+    Ok(true)
+}
+
+/// FreeDesktop: retire le `.trashinfo` réservé pour un `files/<name>` jamais rempli
+fn release_reservation(trashed: &Path) -> Result<bool> {
+    if crate::config::get().storage != Storage::Freedesktop {
+        return Ok(false);
+    }
+    match crate::freedesktop::info_path_for(trashed) {
+        Some(info) if info.exists() => {
+            crate::freedesktop::remove_info(trashed)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
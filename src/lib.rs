@@ -1,8 +1,23 @@
+pub mod config;
+pub mod doctor;
+pub mod filter;
+pub mod freedesktop;
 pub mod fs_safemove;
 pub mod graveyard;
+pub mod history;
 pub mod index;
+pub mod journal;
+pub mod mounts;
+pub mod output;
 pub mod paths;
+pub mod progress;
 pub mod safety;
+pub mod schema;
+pub mod show;
+pub mod size;
+pub mod store;
+pub mod template;
+pub mod tree;
 pub mod ui;
 
 // Re-export pratique pour les tests si besoin :
@@ -1,13 +1,28 @@
 use clap::builder::styling::{AnsiColor, Styles};
-use clap::{ArgAction, ColorChoice, CommandFactory, FromArgMatches, Parser};
+use clap::{ArgAction, ArgGroup, ColorChoice, CommandFactory, FromArgMatches, Parser};
 use std::io::IsTerminal as _;
 use std::path::PathBuf;
 
+mod config;
+mod doctor;
+mod filter;
+mod freedesktop;
 mod fs_safemove;
 mod graveyard;
+mod history;
 mod index;
+mod journal;
+mod mounts;
+mod output;
 mod paths;
+mod progress;
 mod safety;
+mod schema;
+mod show;
+mod size;
+mod store;
+mod template;
+mod tree;
 mod ui;
 
 // Palette de styles pour l'aide Clap (-h/--help)
@@ -25,7 +40,8 @@ fn help_styles() -> Styles {
     version, 
     about = "Safe rm with a graveyard", 
     color = clap::ColorChoice::Auto, 
-    styles = help_styles()
+    styles = help_styles(),
+    group = ArgGroup::new("versioned").args(["resurrect", "show", "cat"]).multiple(true)
 )]
 struct Cli {
     /// Files/dirs to remove (default action)
@@ -54,6 +70,14 @@ struct Cli {
     )]
     resurrect: Option<Option<String>>,
 
+    /// With --resurrect: restore under DIR (or to PATH for a single item) instead of the original place
+    #[arg(long, value_name = "DIR|PATH", requires = "resurrect")]
+    to: Option<PathBuf>,
+
+    /// With --resurrect, when the destination exists: skip, rename, overwrite-bury or fail
+    #[arg(long, value_name = "POLICY", requires = "resurrect", default_value = "fail")]
+    on_conflict: graveyard::OnConflict,
+
     /// (optional) explicit target (used with --cremate/--resurrect)
     #[arg(long = "target", requires = "cremate")]
     target: Option<String>,
@@ -67,6 +91,122 @@ struct Cli {
     #[arg(short = 'l', long = "list")]
     list: bool,
 
+    /// With --list: row template, e.g. "{id} {size:>9} {original_path}" (default: list.format)
+    #[arg(long, value_name = "TEMPLATE", requires = "list")]
+    format: Option<String>,
+
+    /// With --list: strftime format of {deleted_at} (default: list.time_format)
+    #[arg(long, value_name = "STRFTIME", requires = "list")]
+    time_format: Option<String>,
+
+    /// With --list: group entries under their original directories
+    #[arg(long, requires = "list", conflicts_with_all = ["format", "sort", "reverse", "json", "ndjson"])]
+    tree: bool,
+
+    /// With --list: sort by date, name, size, path or kind (default: index order)
+    #[arg(long, value_name = "KEY", requires = "list")]
+    sort: Option<filter::SortKey>,
+
+    /// With --list: reverse the order
+    #[arg(long, requires = "list")]
+    reverse: bool,
+
+    /// With --list: only entries buried less than DURATION ago (e.g. 2h, 7d)
+    #[arg(long, value_name = "DURATION", requires = "list", value_parser = humantime::parse_duration)]
+    since: Option<std::time::Duration>,
+
+    /// With --list: only entries buried more than DURATION ago
+    #[arg(long, value_name = "DURATION", requires = "list", value_parser = humantime::parse_duration)]
+    before: Option<std::time::Duration>,
+
+    /// With --list: only these kinds (file, dir, symlink, other; comma-separated)
+    #[arg(long, value_name = "KIND", requires = "list", value_delimiter = ',')]
+    kind: Vec<index::Kind>,
+
+    /// With --list: only entries whose original path is below DIR
+    #[arg(long, value_name = "DIR", requires = "list", value_parser = filter::parse_under)]
+    under: Option<PathBuf>,
+
+    /// With --list: glob on the name (on the original path if it has a '/'), or re:REGEX
+    #[arg(long = "match", value_name = "PATTERN", requires = "list")]
+    pattern: Option<filter::Pattern>,
+
+    /// With --list: show at most N entries (after sorting)
+    #[arg(long, value_name = "N", requires = "list")]
+    limit: Option<usize>,
+
+    /// Show what a buried entry holds (tree with sizes and mtimes), without restoring it
+    #[arg(long, value_name = "TARGET", conflicts_with_all = ["paths", "cremate", "resurrect", "list"])]
+    show: Option<String>,
+
+    /// Print a buried file's content on stdout
+    #[arg(
+        long,
+        value_name = "TARGET",
+        conflicts_with_all = ["paths", "cremate", "resurrect", "list", "show", "json", "ndjson"]
+    )]
+    cat: Option<String>,
+
+    /// List every buried version of PATH, newest first
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["paths", "cremate", "resurrect", "list", "show", "cat"]
+    )]
+    history: Option<String>,
+
+    /// With --history: diff generation A against B (default 0, the current file), e.g. 2 or 3:1
+    #[arg(
+        long,
+        value_name = "A[:B]",
+        requires = "history",
+        conflicts_with_all = ["json", "ndjson"],
+        value_parser = history::parse_diff
+    )]
+    diff: Option<(usize, usize)>,
+
+    /// With --resurrect/--show/--cat: the Nth previous version of a path buried several times (1 = latest)
+    #[arg(long, value_name = "N", requires = "versioned")]
+    generation: Option<usize>,
+
+    /// With --resurrect/--show/--cat: the version that was in place at TIME (2h, 2025-01-31 18:00, RFC 3339)
+    #[arg(
+        long,
+        value_name = "TIME",
+        requires = "versioned",
+        conflicts_with = "generation",
+        value_parser = history::parse_as_of
+    )]
+    as_of: Option<i64>,
+
+    /// With --resurrect: restore every buried version of TARGET, oldest first, not just the latest
+    #[arg(long, requires = "resurrect", conflicts_with_all = ["generation", "as_of"])]
+    all_generations: bool,
+
+    /// Cremate entries buried for longer than the retention
+    #[arg(long, conflicts_with_all = ["paths", "cremate", "resurrect", "list", "show", "cat", "history"])]
+    prune: bool,
+
+    /// Retention for --prune (e.g. 30d, 12h); default: prune.retention
+    #[arg(long, value_name = "DURATION", requires = "prune", value_parser = humantime::parse_duration)]
+    older_than: Option<std::time::Duration>,
+
+    /// Replay the journal and repair operations interrupted by a crash
+    #[arg(long, conflicts_with_all = ["paths", "cremate", "resurrect", "list", "prune", "show", "cat", "history"])]
+    recover: bool,
+
+    /// Check the index against the graveyard (orphans, dangling entries, temp files)
+    #[arg(long, conflicts_with_all = ["paths", "cremate", "resurrect", "list", "prune", "recover", "show", "cat", "history"])]
+    doctor: bool,
+
+    /// With --doctor: adopt orphans, drop dangling entries, remove stale temp files
+    #[arg(long, requires = "doctor")]
+    fix: bool,
+
+    /// Bury up to N paths concurrently (0 = one per CPU)
+    #[arg(short = 'j', long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
     /// Dry run (no changes)
     #[arg(long)]
     dry_run: bool,
@@ -75,11 +215,49 @@ struct Cli {
     #[arg(short = 'y', long)]
     yes: bool,
 
+    /// Config file (default: ~/.config/nrip/config.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Override the graveyard location
+    #[arg(long, value_name = "DIR")]
+    graveyard_dir: Option<PathBuf>,
+
+    /// Fail at once if another nrip holds the index lock (default: wait lock.timeout)
+    #[arg(long)]
+    no_wait: bool,
+
+    /// Print a JSON document on stdout (schema in the README); messages go to stderr
+    #[arg(long, conflicts_with = "ndjson")]
+    json: bool,
+
+    /// Print one JSON object per line on stdout, as results come
+    #[arg(long)]
+    ndjson: bool,
+
+    /// Colorize output: auto, always or never
+    #[arg(long, value_name = "WHEN")]
+    color: Option<config::ColorMode>,
+
     #[arg(hide = true, long = "__complete", value_names = ["CONTEXT", "PREFIX"], num_args = 1..=2)]
     __complete: Vec<String>,
 }
 
 fn main() -> anyhow::Result<()> {
+    let res = run();
+    // `--json`: le document sort même en cas d'échec
+    output::finish(&res);
+    match res {
+        // Ctrl-C pendant une copie: déjà nettoyé et journalisé, code shell usuel
+        Err(e) if progress::is_cancelled(&e) => {
+            eprintln!("Interrupted.");
+            std::process::exit(130);
+        }
+        res => res,
+    }
+}
+
+fn run() -> anyhow::Result<()> {
     // Politique couleur:
     // - Si NO_COLOR est défini → jamais de couleur
     // - Sinon, Auto (TTY uniquement)
@@ -96,18 +274,41 @@ fn main() -> anyhow::Result<()> {
     let mut matches = cmd.get_matches();
     let cli = Cli::from_arg_matches_mut(&mut matches)?;
 
-    // Config `yansi` (pour nos propres sorties)
-    if no_color_env || !is_tty {
-        yansi::disable();
+    // Config fichier → env (NRIP_*) → CLI
+    let cfg = config::load(&config::Overrides {
+        config: cli.config.clone(),
+        graveyard_dir: cli.graveyard_dir.clone(),
+        color: cli.color,
+        no_wait: cli.no_wait,
+        list_format: cli.format.clone(),
+        time_format: cli.time_format.clone(),
+    })?;
+    let colorize = match cfg.color {
+        config::ColorMode::Always => true,
+        config::ColorMode::Never => false,
+        config::ColorMode::Auto => !no_color_env && is_tty,
+    };
+    config::install(cfg);
+    output::install(if cli.json {
+        output::Format::Json
+    } else if cli.ndjson {
+        output::Format::Ndjson
     } else {
+        output::Format::Human
+    });
+
+    // Config `yansi` (pour nos propres sorties)
+    if colorize {
         yansi::enable();
+    } else {
+        yansi::disable();
     }
     // Internal completion endpoint
     if !cli.__complete.is_empty() {
         let context = cli.__complete[0].as_str();
         let prefix = cli.__complete.get(1).map(|s| s.as_str());
         match context {
-            "cremate" | "resurrect" => {
+            "cremate" | "resurrect" | "show" | "cat" | "history" => {
                 for s in graveyard::completion_candidates(prefix)? {
                     println!("{s}");
                 }
@@ -117,11 +318,40 @@ fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    // RECOVER
+    if cli.recover {
+        output::begin("recover");
+        graveyard::recover_cmd()?;
+        return Ok(());
+    }
+
+    // DOCTOR
+    if cli.doctor {
+        output::begin("doctor");
+        doctor::doctor(cli.fix)?;
+        return Ok(());
+    }
+
+    let version = match (cli.generation, cli.as_of) {
+        (Some(n), _) => Some(history::Version::Nth(n)),
+        (None, Some(t)) => Some(history::Version::AsOf(t)),
+        (None, None) => None,
+    };
+
     // RESURRECT
     if let Some(res_opt) = cli.resurrect {
         // res_opt est déjà un Option<String> : None => interactif ; Some(s) => match par s
         let target = res_opt;
-        graveyard::resurrect_cmd(target, cli.dry_run, cli.yes)?;
+        output::begin("resurrect");
+        graveyard::resurrect_cmd(
+            target,
+            cli.to,
+            cli.on_conflict,
+            version,
+            cli.all_generations,
+            cli.dry_run,
+            cli.yes,
+        )?;
         return Ok(());
     }
 
@@ -132,19 +362,63 @@ fn main() -> anyhow::Result<()> {
             (None, Some(t)) => Some(t), 
             (None, None) => None,               
         };
+        output::begin("cremate");
         graveyard::cremate(target, cli.dry_run, cli.yes)?;
         return Ok(());
     }
 
+    // SHOW / CAT
+    if let Some(target) = cli.show {
+        output::begin("show");
+        show::show_cmd(&target, version)?;
+        return Ok(());
+    }
+    if let Some(target) = cli.cat {
+        output::begin("cat");
+        show::cat_cmd(&target, version)?;
+        return Ok(());
+    }
+
+    // HISTORY
+    if let Some(target) = cli.history {
+        output::begin("history");
+        match cli.diff {
+            Some(pair) => history::diff_cmd(&target, pair)?,
+            None => history::history_cmd(&target)?,
+        }
+        return Ok(());
+    }
+
+    // PRUNE
+    if cli.prune {
+        output::begin("prune");
+        graveyard::prune(cli.older_than, cli.dry_run, cli.yes)?;
+        return Ok(());
+    }
+
     // LIST
     if cli.list {
-        graveyard::list()?;
+        output::begin("list");
+        graveyard::list(&filter::Filter {
+            since: cli.since,
+            before: cli.before,
+            kinds: cli.kind,
+            under: cli.under,
+            pattern: cli.pattern,
+            sort: cli.sort,
+            reverse: cli.reverse,
+            limit: cli.limit,
+        }, cli.tree)?;
         return Ok(());
     }
 
     // Default action: bury paths
     if !cli.paths.is_empty() {
-        graveyard::bury(&cli.paths, cli.force)?;
+        output::begin("bury");
+        graveyard::bury(&cli.paths, cli.force, cli.jobs)?;
+        if config::get().prune.auto {
+            graveyard::auto_prune()?;
+        }
         return Ok(());
     }
 
@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use fs_err as fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+/// Préfixe des graveyards créés au sommet des autres points de montage
+pub const MOUNT_GRAVEYARD_PREFIX: &str = ".nrip-graveyard-";
+
+/// `.nrip-graveyard-$UID`
+pub fn mount_graveyard_name() -> String {
+    #[cfg(unix)]
+    {
+        format!(
+            "{MOUNT_GRAVEYARD_PREFIX}{}",
+            rustix::process::getuid().as_raw()
+        )
+    }
+    #[cfg(windows)]
+    {
+        format!("{MOUNT_GRAVEYARD_PREFIX}0")
+    }
+}
+
+/// st_dev du chemin, ou du premier ancêtre existant
+#[cfg(unix)]
+fn device_of(p: &Path) -> Result<u64> {
+    let mut cur = Some(p);
+    while let Some(c) = cur {
+        if let Ok(md) = std::fs::symlink_metadata(c) {
+            return Ok(md.dev());
+        }
+        cur = c.parent();
+    }
+    anyhow::bail!("no existing ancestor for {}", p.display())
+}
+
+/// Sommet du point de montage contenant `dir` (on remonte tant que st_dev ne change pas)
+#[cfg(unix)]
+pub fn mount_root(dir: &Path) -> Result<PathBuf> {
+    let mut cur = fs::canonicalize(dir)?;
+    let dev = fs::metadata(&cur)?.dev();
+    while let Some(parent) = cur.parent() {
+        match std::fs::metadata(parent) {
+            Ok(md) if md.dev() == dev => cur = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    Ok(cur)
+}
+
+/// `dir` est-il le sommet de son point de montage ?
+#[cfg(unix)]
+pub fn is_mount_top(dir: &Path) -> bool {
+    match (mount_root(dir), std::fs::canonicalize(dir)) {
+        (Ok(top), Ok(dir)) => top == dir,
+        _ => false,
+    }
+}
+
+/// `src` (absolu) est-il sur un autre device que `home`?
+#[cfg(unix)]
+pub fn on_other_device(src: &Path, home: &Path) -> Result<bool> {
+    // l'entrée de répertoire vit sur le FS du parent
+    let parent = src.parent().unwrap_or(Path::new("/"));
+    Ok(device_of(parent)? != device_of(home)?)
+}
+
+/// Crée au besoin un dossier privé (0700, à nous, pas un symlink, inscriptible)
+#[cfg(unix)]
+pub fn ensure_private_dir(gy: &Path) -> Result<PathBuf> {
+    let gy = gy.to_path_buf();
+    match std::fs::DirBuilder::new().mode(0o700).create(&gy) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e).with_context(|| format!("mkdir {}", gy.display())),
+    }
+    let md = fs::symlink_metadata(&gy)?;
+    if !md.is_dir() || md.file_type().is_symlink() {
+        anyhow::bail!("{} is not a directory", gy.display());
+    }
+    if md.uid() != rustix::process::getuid().as_raw() {
+        anyhow::bail!("{} is not owned by the current user", gy.display());
+    }
+    rustix::fs::access(&gy, rustix::fs::Access::WRITE_OK)
+        .with_context(|| format!("{} is not writable", gy.display()))?;
+    Ok(gy)
+}
+
+/// Graveyard cible pour `src` (absolu): celui de son point de montage si `src`
+/// n'est pas sur le même device que `home_gy`, sinon `home_gy`.
+/// Toute erreur côté montage → repli silencieux sur `home_gy`.
+pub fn graveyard_for(src: &Path, home_gy: &Path) -> Result<PathBuf> {
+    #[cfg(unix)]
+    {
+        if !crate::config::get().per_mount_graveyards {
+            return Ok(home_gy.to_path_buf());
+        }
+        if !on_other_device(src, home_gy)? {
+            return Ok(home_gy.to_path_buf());
+        }
+        let parent = src.parent().unwrap_or(Path::new("/"));
+        let gy = mount_root(parent)
+            .and_then(|top| ensure_private_dir(&top.join(mount_graveyard_name())));
+        Ok(gy.unwrap_or_else(|_| home_gy.to_path_buf()))
+    }
+    #[cfg(windows)]
+    {
+        let _ = src;
+        Ok(home_gy.to_path_buf())
+    }
+}
+
+/// Le chemin est-il (dans) un graveyard de point de montage ?
+pub fn in_mount_graveyard(p: &Path) -> bool {
+    p.components().any(|c| {
+        c.as_os_str()
+            .to_str()
+            .is_some_and(|s| s.starts_with(MOUNT_GRAVEYARD_PREFIX))
+    })
+}
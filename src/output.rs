@@ -0,0 +1,170 @@
+//! Sorties machine (`--json`, `--ndjson`) au schéma stable décrit dans le README.
+//! En mode machine, stdout ne porte que du JSON: les messages humains passent sur stderr.
+
+use chrono::{TimeZone, Utc};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use crate::index::{Entry, Kind};
+
+/// Version du schéma des documents publiés (indépendante de celle de l'index)
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Human,
+    /// Un document à la fin de la commande
+    Json,
+    /// Un objet par ligne, au fil de l'eau
+    Ndjson,
+}
+
+static FORMAT: OnceLock<Format> = OnceLock::new();
+static COMMAND: OnceLock<&'static str> = OnceLock::new();
+/// Enregistrements accumulés pour le document `--json`
+static RECORDS: Mutex<Vec<Value>> = Mutex::new(Vec::new());
+
+pub fn install(f: Format) {
+    let _ = FORMAT.set(f);
+}
+
+pub fn format() -> Format {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+pub fn is_machine() -> bool {
+    format() != Format::Human
+}
+
+/// Nom de la commande, repris dans le document final
+pub fn begin(command: &'static str) {
+    let _ = COMMAND.set(command);
+}
+
+/// `println!` en mode humain, `eprintln!` en mode machine
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::output::is_machine() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+pub(crate) use say;
+
+/// Entrée du graveyard telle que publiée (`list`, et `entry` des résultats)
+#[derive(Serialize)]
+pub struct EntryRecord<'a> {
+    pub id: String,
+    pub original_path: &'a Path,
+    pub trashed_path: &'a Path,
+    pub deleted_at: i64,
+    pub deleted_at_rfc3339: String,
+    pub kind: &'static str,
+    pub size: u64,
+    pub disk_usage: u64,
+    pub graveyard: Option<&'a Path>,
+}
+
+impl<'a> EntryRecord<'a> {
+    pub fn new(e: &'a Entry, id: String) -> Self {
+        let usage = crate::size::entry_usage(e);
+        EntryRecord {
+            id,
+            original_path: &e.original_path,
+            trashed_path: &e.trashed_path,
+            deleted_at: e.deleted_at,
+            deleted_at_rfc3339: Utc
+                .timestamp_opt(e.deleted_at, 0)
+                .single()
+                .map(|d| d.to_rfc3339())
+                .unwrap_or_default(),
+            kind: kind_name(e.kind),
+            size: usage.apparent,
+            disk_usage: usage.disk,
+            graveyard: e.graveyard.as_deref(),
+        }
+    }
+}
+
+/// `kind` dans le schéma JSON
+pub fn kind_name(k: Kind) -> &'static str {
+    match k {
+        Kind::File => "file",
+        Kind::Dir => "dir",
+        Kind::Symlink => "symlink",
+        Kind::Other => "other",
+    }
+}
+
+/// Publie un enregistrement (rien en mode humain)
+pub fn emit(v: Value) {
+    match format() {
+        Format::Human => {}
+        Format::Ndjson => println!("{v}"),
+        Format::Json => RECORDS.lock().unwrap_or_else(|e| e.into_inner()).push(v),
+    }
+}
+
+/// Élément traité: `{"action": "buried", "entry": {...}}`
+pub fn done(action: &str, e: &Entry, id: String) {
+    if is_machine() {
+        emit(json!({ "action": action, "entry": EntryRecord::new(e, id) }));
+    }
+}
+
+/// Élément déplacé ailleurs que son chemin d'origine: `path` donne où il est arrivé
+pub fn moved(action: &str, e: &Entry, id: String, to: &Path) {
+    if is_machine() {
+        emit(json!({ "action": action, "entry": EntryRecord::new(e, id), "path": to }));
+    }
+}
+
+/// Chemin sorti d'un dossier enterré: `entry` est le dossier, toujours au graveyard
+pub fn extracted(e: &Entry, id: String, inner: &Path, to: &Path) {
+    if is_machine() {
+        emit(json!({
+            "action": "extracted",
+            "entry": EntryRecord::new(e, id),
+            "inner_path": inner,
+            "path": to,
+        }));
+    }
+}
+
+/// Élément en échec, les autres continuent: `{"action": "failed", "path": ..., "error": ...}`
+pub fn failed(path: &Path, err: &dyn std::fmt::Display) {
+    if is_machine() {
+        emit(json!({ "action": "failed", "path": path, "error": format!("{err:#}") }));
+    }
+}
+
+/// Clôt la commande: document `--json` complet, ou ligne `{"error": ...}` en NDJSON
+pub fn finish(res: &anyhow::Result<()>) {
+    let error = res.as_ref().err().map(|e| format!("{e:#}"));
+    match format() {
+        Format::Human => {}
+        Format::Ndjson => {
+            if let Some(error) = error {
+                println!("{}", json!({ "error": error }));
+            }
+        }
+        Format::Json => {
+            let command = COMMAND.get().copied().unwrap_or("help");
+            let records = std::mem::take(&mut *RECORDS.lock().unwrap_or_else(|e| e.into_inner()));
+            let key = if matches!(command, "list" | "show" | "history") { "items" } else { "results" };
+            let doc = json!({
+                "version": SCHEMA_VERSION,
+                "command": command,
+                "ok": error.is_none(),
+                "error": error,
+                key: records,
+            });
+            println!("{doc:#}");
+        }
+    }
+}
@@ -5,3 +5,11 @@ pub fn data_dir() -> Result<PathBuf> {
     let base = dirs::data_dir().context("XDG data dir not found")?;
     Ok(base.join("nrip"))
 }
+
+/// `graveyard_dir` de la config, sinon `data_dir()/graveyard`
+pub fn graveyard_dir() -> Result<PathBuf> {
+    match &crate::config::get().graveyard_dir {
+        Some(d) => Ok(d.clone()),
+        None => Ok(data_dir()?.join("graveyard")),
+    }
+}
@@ -0,0 +1,183 @@
+//! Progression des copies longues (stderr, TTY uniquement) et interruption par Ctrl-C.
+
+use std::io::IsTerminal as _;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::size::human_size;
+
+/// Levé par SIGINT pendant une copie; lu par les boucles de copie
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Erreur d'une copie interrompue par l'utilisateur (Ctrl-C)
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("interrupted")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// `Err(Cancelled)` si Ctrl-C a été pressé depuis le début de la copie
+pub fn check_interrupt() -> anyhow::Result<()> {
+    if INTERRUPTED.load(Ordering::Relaxed) {
+        return Err(Cancelled.into());
+    }
+    Ok(())
+}
+
+pub fn is_cancelled(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<Cancelled>().is_some()
+}
+
+/// Capte SIGINT le temps d'une copie, puis rend l'ancien comportement.
+/// Ré-entrant: les gardes imbriquées (ou d'autres fils) partagent le handler,
+/// le dernier `drop` restaure l'action d'origine.
+pub struct InterruptGuard(());
+
+#[cfg(unix)]
+struct Installed {
+    depth: usize,
+    previous: Option<libc::sigaction>,
+}
+
+#[cfg(unix)]
+static INSTALLED: std::sync::Mutex<Installed> = std::sync::Mutex::new(Installed {
+    depth: 0,
+    previous: None,
+});
+
+#[cfg(unix)]
+extern "C" fn on_sigint(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+impl InterruptGuard {
+    pub fn install() -> InterruptGuard {
+        #[cfg(unix)]
+        {
+            let mut st = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+            if st.depth == 0 {
+                INTERRUPTED.store(false, Ordering::Relaxed);
+                // SAFETY: le handler ne fait qu'un store atomique (async-signal-safe)
+                unsafe {
+                    let mut action: libc::sigaction = std::mem::zeroed();
+                    action.sa_sigaction = on_sigint as *const () as usize;
+                    action.sa_flags = libc::SA_RESTART;
+                    libc::sigemptyset(&mut action.sa_mask);
+                    let mut previous: libc::sigaction = std::mem::zeroed();
+                    libc::sigaction(libc::SIGINT, &action, &mut previous);
+                    st.previous = Some(previous);
+                }
+            }
+            st.depth += 1;
+        }
+        #[cfg(windows)]
+        INTERRUPTED.store(false, Ordering::Relaxed);
+        InterruptGuard(())
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        {
+            let mut st = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+            st.depth -= 1;
+            if st.depth == 0
+                && let Some(previous) = st.previous.take()
+            {
+                // SAFETY: remet l'action sauvegardée par `install`
+                unsafe {
+                    libc::sigaction(libc::SIGINT, &previous, std::ptr::null_mut());
+                }
+            }
+        }
+    }
+}
+
+/// Coupe l'affichage (copies concurrentes: les lignes se chevaucheraient)
+static HIDDEN: AtomicBool = AtomicBool::new(false);
+
+pub fn hide() {
+    HIDDEN.store(true, Ordering::Relaxed);
+}
+
+/// Compteurs d'une copie en cours, partagés avec le fil d'affichage
+pub struct Progress {
+    label: String,
+    total: u64,
+    bytes: AtomicU64,
+    files: AtomicU64,
+    start: Instant,
+}
+
+impl Progress {
+    pub fn add_bytes(&self, n: u64) {
+        self.bytes.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn add_file(&self) {
+        self.files.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// "copying foo: 1.2 GiB / 3.4 GiB (35%) · 120 files · 250.0 MiB/s · ETA 9s"
+    fn line(&self) -> String {
+        let done = self.bytes.load(Ordering::Relaxed);
+        let files = self.files.load(Ordering::Relaxed);
+        let secs = self.start.elapsed().as_secs_f64().max(0.001);
+        let rate = done as f64 / secs;
+        let pct = (done as f64 * 100.0 / self.total.max(1) as f64).min(100.0);
+        let eta = if rate > 0.0 && self.total > done {
+            let left = Duration::from_secs(((self.total - done) as f64 / rate).ceil() as u64);
+            humantime::format_duration(left).to_string()
+        } else {
+            "-".into()
+        };
+        format!(
+            "copying {}: {} / {} ({pct:.0}%) · {files} files · {}/s · ETA {eta}",
+            self.label,
+            human_size(done),
+            human_size(self.total),
+            human_size(rate as u64),
+        )
+    }
+}
+
+/// Exécute `f` en affichant sa progression sur stderr (rafraîchie, TTY uniquement;
+/// rien pour les copies de moins d'une demi-seconde). `total` n'est calculé que si affiché.
+pub fn track<T>(label: &str, total: impl FnOnce() -> u64, f: impl FnOnce(&Progress) -> T) -> T {
+    let show = !HIDDEN.load(Ordering::Relaxed) && std::io::stderr().is_terminal();
+    let p = Progress {
+        label: label.to_string(),
+        total: if show { total() } else { 0 },
+        bytes: AtomicU64::new(0),
+        files: AtomicU64::new(0),
+        start: Instant::now(),
+    };
+    if !show {
+        return f(&p);
+    }
+    let finished = AtomicBool::new(false);
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            let mut shown = false;
+            while !finished.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(100));
+                if p.start.elapsed() >= Duration::from_millis(500) {
+                    eprint!("\r\x1b[2K{}", p.line());
+                    shown = true;
+                }
+            }
+            if shown {
+                eprint!("\r\x1b[2K");
+            }
+        });
+        let out = f(&p);
+        finished.store(true, Ordering::Relaxed);
+        out
+    })
+}
@@ -21,14 +21,32 @@ pub struct SafetyCtx {
 
 fn is_index_like(p: &Path) -> bool {
     if let Some(name) = p.file_name().and_then(|s| s.to_str()) {
-        name == "index.json" || name == ".index.lock"
+        matches!(name, "index.json" | "index.log" | ".index.lock")
     } else {
         false
     }
 }
 
 fn is_journal(p: &Path) -> bool {
-    p.file_name().and_then(|s| s.to_str()) == Some(".journal")
+    matches!(
+        p.file_name().and_then(|s| s.to_str()),
+        Some(".journal" | ".journal.1")
+    )
+}
+
+/// `$topdir/.Trash-$uid` (corbeilles FreeDesktop de montage): seulement au sommet d'un montage
+#[cfg(unix)]
+fn in_mount_trash(p: &Path) -> bool {
+    let name = format!(".Trash-{}", rustix::process::getuid().as_raw());
+    p.ancestors().any(|a| {
+        a.file_name().is_some_and(|n| n == name.as_str())
+            && a.parent().is_some_and(crate::mounts::is_mount_top)
+    })
+}
+
+#[cfg(windows)]
+fn in_mount_trash(_: &Path) -> bool {
+    false
 }
 
 pub fn classify_forbid(p: &Path, ctx: &SafetyCtx) -> Option<Forbid> {
@@ -51,6 +69,16 @@ pub fn classify_forbid(p: &Path, ctx: &SafetyCtx) -> Option<Forbid> {
     if p.starts_with(&ctx.graveyard) {
         return Some(Forbid::InsideGraveyard);
     }
+    if crate::mounts::in_mount_graveyard(p) || in_mount_trash(p) {
+        let is_root = p.file_name().and_then(|s| s.to_str()).is_some_and(|s| {
+            s.starts_with(crate::mounts::MOUNT_GRAVEYARD_PREFIX) || s.starts_with(".Trash-")
+        });
+        return Some(if is_root {
+            Forbid::GraveyardItself
+        } else {
+            Forbid::InsideGraveyard
+        });
+    }
     if is_index_like(p) {
         return Some(Forbid::IndexFile);
     }
@@ -71,7 +99,7 @@ pub fn guard_path(p: &Path, ctx: &SafetyCtx) -> anyhow::Result<()> {
                 Forbid::DotDot => "denied: '..' is not allowed",
                 Forbid::GraveyardItself => "denied: target is the graveyard itself",
                 Forbid::InsideGraveyard => "denied: item is inside the graveyard",
-                Forbid::IndexFile => "denied: target is index.json/.index.lock",
+                Forbid::IndexFile => "denied: target is the index (index.json/index.log/.index.lock)",
                 Forbid::JournalFile => "denied: target is .journal",
                 Forbid::Empty => "denied: empty path",
             };
@@ -0,0 +1,86 @@
+//! Version du format de l'index sur disque et migrations successives.
+//! Un document `{"version": n, "items": [...]}` passe de n à n+1 par `MIGRATIONS[n]`;
+//! un index sans `version` est en v0.
+
+use anyhow::{Context, Result, bail};
+use fs_err as fs;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::index::Kind;
+
+/// Version écrite par ce binaire
+pub const CURRENT: u32 = 1;
+
+/// `MIGRATIONS[n]`: v(n) → v(n+1)
+const MIGRATIONS: &[fn(&mut Value) -> Result<()>] = &[v0_to_v1];
+
+/// v0: `kind` pouvait manquer (index d'avant son ajout); on le relit sur le fichier enterré
+fn v0_to_v1(doc: &mut Value) -> Result<()> {
+    for e in items_mut(doc)? {
+        if e.get("kind").is_none() {
+            let kind = e["trashed_path"]
+                .as_str()
+                .and_then(|p| std::fs::symlink_metadata(p).ok())
+                .map_or(Kind::Other, |m| Kind::from_file_type(m.file_type()));
+            e["kind"] = serde_json::to_value(kind)?;
+        }
+    }
+    Ok(())
+}
+
+fn items_mut(doc: &mut Value) -> Result<&mut Vec<Value>> {
+    doc.get_mut("items")
+        .and_then(Value::as_array_mut)
+        .context("index: missing `items` array")
+}
+
+/// Version annoncée par un document (absente = 0)
+pub fn version_of(doc: &Value) -> Result<u32> {
+    match doc.get("version") {
+        None => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .with_context(|| format!("index: invalid schema version {v}")),
+    }
+}
+
+/// Refuse un index écrit par un nrip plus récent: le réécrire perdrait ce qu'on ne connaît pas
+pub fn check(version: u32, source: &Path) -> Result<()> {
+    if version > CURRENT {
+        bail!(
+            "{} uses index schema v{version}, but this nrip only supports up to v{CURRENT}; \
+             upgrade nrip (the index was left untouched)",
+            source.display()
+        );
+    }
+    Ok(())
+}
+
+/// Amène `doc` à la version courante; renvoie la version trouvée
+pub fn upgrade(doc: &mut Value, source: &Path) -> Result<u32> {
+    let found = version_of(doc)?;
+    check(found, source)?;
+    for m in &MIGRATIONS[found as usize..] {
+        m(doc).with_context(|| format!("migrate {}", source.display()))?;
+    }
+    doc["version"] = CURRENT.into();
+    Ok(found)
+}
+
+/// Copie `<index>.v<N>.bak` avant la première réécriture au format courant
+pub fn backup(path: &Path, version: u32) -> Result<PathBuf> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".v{version}.bak"));
+    let bak = PathBuf::from(name);
+    if !bak.exists() {
+        fs::copy(path, &bak)?;
+    }
+    eprintln!(
+        "upgraded {} to index schema v{CURRENT} (backup: {})",
+        path.display(),
+        bak.display()
+    );
+    Ok(bak)
+}
@@ -0,0 +1,230 @@
+//! `nrip --show` / `--cat`: regarder dans le graveyard sans rien restaurer.
+//! TARGET se résout comme pour `--resurrect` (nom, préfixe d'ID, ou `dossier/chemin`),
+//! `--generation`/`--as-of` compris.
+
+use anyhow::{Result, bail};
+use chrono::{Local, TimeZone, Utc};
+use fs_err as fs;
+use serde_json::json;
+use std::io;
+use std::path::{Path, PathBuf};
+use yansi::Paint;
+
+use crate::graveyard::{compact_age, display_id, find_inner, kind_icon, match_target};
+use crate::history::{Version, narrow};
+use crate::index::{self, Entry, Kind};
+use crate::output::{self, say};
+use crate::size::human_size;
+
+/// Entrée désignée par TARGET, et chemin relatif à l'intérieur (vide = l'entrée entière).
+/// Plusieurs versions d'un même chemin: `version`, sinon la dernière.
+fn resolve(target: &str, version: Option<Version>) -> Result<(Entry, PathBuf)> {
+    let entries = index::load_entries()?;
+    if version.is_none()
+        && target.contains('/')
+        && let Some((i, rel)) = find_inner(&entries, target)
+    {
+        return Ok((entries[i].clone(), rel));
+    }
+    match narrow(match_target(&entries, target), version, false)?.as_slice() {
+        [] => bail!("No graveyard entry matches '{target}'"),
+        [e] => Ok((e.clone(), PathBuf::new())),
+        many => {
+            eprintln!("Multiple matches:");
+            for m in many {
+                eprintln!("  {:7}  {}", display_id(m), index::basename_of_original(m));
+            }
+            bail!("'{target}' matches {} entries; pick one by ID", many.len())
+        }
+    }
+}
+
+/// `base/rel`, sans `/` final quand `rel` est vide
+fn inside(base: &Path, rel: &Path) -> PathBuf {
+    if rel.as_os_str().is_empty() {
+        base.to_path_buf()
+    } else {
+        base.join(rel)
+    }
+}
+
+/// Un chemin du graveyard et son contenu, tailles cumulées
+struct Node {
+    name: String,
+    kind: Kind,
+    /// Taille apparente, sous-arbre compris
+    size: u64,
+    mtime: i64,
+    link: Option<PathBuf>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn read(path: &Path, name: String) -> Result<Node> {
+        let md = fs::symlink_metadata(path)?;
+        let kind = Kind::from_file_type(md.file_type());
+        let mut children = Vec::new();
+        if md.is_dir() {
+            let mut names: Vec<_> = fs::read_dir(path)?
+                .filter_map(|d| d.ok())
+                .map(|d| d.file_name())
+                .collect();
+            names.sort();
+            for n in names {
+                children.push(Node::read(&path.join(&n), n.to_string_lossy().into_owned())?);
+            }
+        }
+        Ok(Node {
+            name,
+            kind,
+            size: children.iter().fold(md.len(), |acc, c| acc.saturating_add(c.size)),
+            mtime: md
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs() as i64),
+            link: md.is_symlink().then(|| std::fs::read_link(path).ok()).flatten(),
+            children,
+        })
+    }
+
+    fn count(&self) -> (usize, usize) {
+        self.children.iter().fold((0, 0), |(files, dirs), c| {
+            let (f, d) = c.count();
+            match c.kind {
+                Kind::Dir => (files + f, dirs + d + 1),
+                _ => (files + f + 1, dirs + d),
+            }
+        })
+    }
+}
+
+/// `--show`: arbre du dossier enterré (ou l'élément seul), tailles et dates de modification
+pub fn show_cmd(target: &str, version: Option<Version>) -> Result<()> {
+    let (e, rel) = resolve(target, version)?;
+    let trashed = inside(&e.trashed_path, &rel);
+    let original = inside(&e.original_path, &rel);
+    let name = original
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| original.display().to_string());
+    let root = Node::read(&trashed, name)?;
+
+    if output::is_machine() {
+        emit(&root, &original, &trashed);
+        return Ok(());
+    }
+
+    let age = compact_age(Utc::now().timestamp().saturating_sub(e.deleted_at).max(0) as u64);
+    if rel.as_os_str().is_empty() {
+        say!("{} {}  ({}, buried {age} ago)", kind_icon(root.kind), original.display(), display_id(&e));
+    } else {
+        say!(
+            "{} {}  (inside {}, buried {age} ago)",
+            kind_icon(root.kind),
+            original.display(),
+            display_id(&e)
+        );
+    }
+
+    let mut rows = Vec::new();
+    rows.push(row(String::new(), &root));
+    push_children(&mut rows, "", &root);
+    let width = rows.iter().map(|(l, _, _)| l.chars().count()).max().unwrap_or(0);
+    let time_format = &crate::config::get().list.time_format;
+    for (left, node, painted) in &rows {
+        let pad = " ".repeat(width - left.chars().count());
+        say!(
+            "{painted}{pad}  {:>10}  {}",
+            human_size(node.size).yellow(),
+            when(node.mtime, time_format).dim()
+        );
+    }
+    if root.kind == Kind::Dir {
+        let (files, dirs) = root.count();
+        say!("{files} file(s), {dirs} dir(s), {}", human_size(root.size));
+    }
+    Ok(())
+}
+
+/// (texte brut pour l'alignement, nœud, texte coloré)
+type Row<'a> = (String, &'a Node, String);
+
+fn row(lead: String, node: &Node) -> Row<'_> {
+    let (plain, painted) = match (&node.link, node.kind) {
+        (Some(to), _) => {
+            let s = format!("{} -> {}", node.name, to.display());
+            (s.clone(), s.cyan().to_string())
+        }
+        (None, Kind::Dir) => {
+            let s = format!("{}/", node.name);
+            (s.clone(), s.blue().bold().to_string())
+        }
+        (None, _) => (node.name.clone(), node.name.clone()),
+    };
+    (format!("{lead}{plain}"), node, format!("{lead}{painted}"))
+}
+
+fn push_children<'a>(rows: &mut Vec<Row<'a>>, indent: &str, node: &'a Node) {
+    let total = node.children.len();
+    for (i, child) in node.children.iter().enumerate() {
+        let (lead, next) = if i + 1 == total {
+            (format!("{indent}└── "), format!("{indent}    "))
+        } else {
+            (format!("{indent}├── "), format!("{indent}│   "))
+        };
+        rows.push(row(lead, child));
+        push_children(rows, &next, child);
+    }
+}
+
+fn when(ts: i64, fmt: &str) -> String {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|d| d.format(fmt).to_string())
+        .unwrap_or_default()
+}
+
+/// `--json`: un élément par chemin, `path` étant là où `-r` le restaurerait
+fn emit(node: &Node, original: &Path, trashed: &Path) {
+    output::emit(json!({
+        "path": original,
+        "trashed_path": trashed,
+        "kind": output::kind_name(node.kind),
+        "size": node.size,
+        "mtime": node.mtime,
+        "mtime_rfc3339": Utc
+            .timestamp_opt(node.mtime, 0)
+            .single()
+            .map(|d| d.to_rfc3339())
+            .unwrap_or_default(),
+    }));
+    for c in &node.children {
+        emit(c, &original.join(&c.name), &trashed.join(&c.name));
+    }
+}
+
+/// `--cat`: contenu d'un fichier enterré sur stdout, tel quel
+pub fn cat_cmd(target: &str, version: Option<Version>) -> Result<()> {
+    let (e, rel) = resolve(target, version)?;
+    let path = inside(&e.trashed_path, &rel);
+    let shown = inside(&e.original_path, &rel);
+    let md = fs::symlink_metadata(&path)?;
+    if md.is_dir() {
+        bail!("{} is a directory; use --show to list it", shown.display());
+    }
+    if md.is_symlink() {
+        let to = std::fs::read_link(&path)?;
+        bail!("{} is a symlink (-> {}); nothing to print", shown.display(), to.display());
+    }
+    if !md.is_file() {
+        bail!("{} is not a regular file", shown.display());
+    }
+    let mut f = fs::File::open(&path)?;
+    match io::copy(&mut f, &mut io::stdout().lock()) {
+        // `nrip --cat x | head`: le lecteur a fini, pas nous
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        res => res.map(|_| ()).map_err(Into::into),
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::{Result, bail};
+use std::collections::HashSet;
+use std::path::Path;
+
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+use crate::index::Entry;
+
+/// Occupation d'un arbre: taille apparente (`du -sb`) et blocs alloués (`du -s`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub apparent: u64,
+    pub disk: u64,
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, rhs: Usage) {
+        self.apparent = self.apparent.saturating_add(rhs.apparent);
+        self.disk = self.disk.saturating_add(rhs.disk);
+    }
+}
+
+/// Parcourt `p` sans suivre les symlinks; un inode à liens multiples n'est compté qu'une fois
+pub fn measure(p: &Path) -> Usage {
+    let mut seen = HashSet::new();
+    let mut u = Usage::default();
+    walk(p, &mut seen, &mut u);
+    u
+}
+
+fn walk(p: &Path, seen: &mut HashSet<(u64, u64)>, u: &mut Usage) {
+    let Ok(md) = std::fs::symlink_metadata(p) else {
+        return;
+    };
+    #[cfg(unix)]
+    {
+        if !md.is_dir() && md.nlink() > 1 && !seen.insert((md.dev(), md.ino())) {
+            return;
+        }
+        *u += Usage {
+            apparent: md.len(),
+            disk: md.blocks().saturating_mul(512),
+        };
+    }
+    #[cfg(windows)]
+    {
+        let _ = &seen;
+        *u += Usage {
+            apparent: md.len(),
+            disk: md.len(),
+        };
+    }
+    if md.is_dir()
+        && let Ok(rd) = std::fs::read_dir(p)
+    {
+        for entry in rd.filter_map(|r| r.ok()) {
+            walk(&entry.path(), seen, u);
+        }
+    }
+}
+
+/// Occupation enregistrée dans l'index, sinon mesurée sur place (anciens index, corbeille)
+pub fn entry_usage(e: &Entry) -> Usage {
+    match (e.size, e.disk_usage) {
+        (Some(apparent), Some(disk)) => Usage { apparent, disk },
+        _ => measure(&e.trashed_path),
+    }
+}
+
+/// "10GiB", "500M", "1.5G", "2000KB", "4096" → octets (K/M/G/T et *iB = 1024, *B = 1000)
+pub fn parse_size(s: &str) -> Result<u64> {
+    let t = s.trim();
+    let split = t
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(t.len());
+    let (num, unit) = t.split_at(split);
+    let n: f64 = match num.parse() {
+        Ok(n) => n,
+        Err(_) => bail!("invalid size '{s}'"),
+    };
+    let mult: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        other => bail!("invalid size unit '{other}' in '{s}'"),
+    };
+    Ok((n * mult as f64) as u64)
+}
+
+/// 1536 → "1.5 KiB"
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut v = bytes as f64;
+    let mut u = 0;
+    while v >= 1024.0 && u < UNITS.len() - 1 {
+        v /= 1024.0;
+        u += 1;
+    }
+    if u == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{v:.1} {}", UNITS[u])
+    }
+}
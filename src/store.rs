@@ -0,0 +1,322 @@
+//! Persistance de l'index: `index.json` (document complet, réécrit à chaque transaction)
+//! ou `index.log` (opérations ajoutées en fin de fichier, compacté de temps en temps).
+//! Choix par `index.backend`; si seul l'autre fichier existe, il est importé une fois
+//! puis renommé en `*.migrated`.
+
+use anyhow::{Context, Result};
+use fs_err as fs;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+use crate::config::IndexBackend;
+use crate::index::{Entry, Index};
+use crate::schema;
+
+pub trait Store {
+    /// État courant (vide si rien n'a encore été écrit)
+    fn load(&mut self) -> Result<Index>;
+    /// Persiste `after`; `before` est ce que `load` a rendu dans la même transaction
+    fn save(&mut self, before: &Index, after: &Index) -> Result<()>;
+}
+
+pub fn file_name(backend: IndexBackend) -> &'static str {
+    match backend {
+        IndexBackend::Json => "index.json",
+        IndexBackend::Log => "index.log",
+    }
+}
+
+fn backend_at(backend: IndexBackend, dir: &Path) -> Box<dyn Store> {
+    let path = dir.join(file_name(backend));
+    match backend {
+        IndexBackend::Json => Box::new(JsonStore {
+            path,
+            version: None,
+        }),
+        IndexBackend::Log => Box::new(LogStore {
+            path,
+            version: None,
+            lines: 0,
+            valid_len: 0,
+        }),
+    }
+}
+
+fn other(backend: IndexBackend) -> IndexBackend {
+    match backend {
+        IndexBackend::Json => IndexBackend::Log,
+        IndexBackend::Log => IndexBackend::Json,
+    }
+}
+
+/// Store à lire: celui configuré, ou l'ancien tant que la migration n'a pas eu lieu
+pub fn open(dir: &Path) -> Box<dyn Store> {
+    let wanted = crate::config::get().index.backend;
+    if !dir.join(file_name(wanted)).exists() && dir.join(file_name(other(wanted))).exists() {
+        return backend_at(other(wanted), dir);
+    }
+    backend_at(wanted, dir)
+}
+
+/// Store configuré, après import éventuel de l'autre format (sous verrou d'écriture)
+pub fn open_migrated(dir: &Path) -> Result<Box<dyn Store>> {
+    let wanted = crate::config::get().index.backend;
+    let old = dir.join(file_name(other(wanted)));
+    let mut store = backend_at(wanted, dir);
+    if dir.join(file_name(wanted)).exists() || !old.exists() {
+        return Ok(store);
+    }
+    let idx = backend_at(other(wanted), dir).load()?;
+    store.save(&Index::default(), &idx)?;
+    let mut parked = old.clone().into_os_string();
+    parked.push(".migrated");
+    fs::rename(&old, &parked)?;
+    eprintln!(
+        "migrated {} -> {} ({} entries)",
+        file_name(other(wanted)),
+        file_name(wanted),
+        idx.items.len()
+    );
+    Ok(store)
+}
+
+/// tmp + fsync + rename + fsync(dir)
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut tmp = NamedTempFile::new_in(dir).context("mkstemp in index dir")?;
+    tmp.write_all(data).context("write tmp")?;
+    tmp.as_file().sync_all().context("fsync tmp")?;
+    let tmp_path = tmp.into_temp_path();
+    tmp_path.persist(path).map_err(|e| {
+        anyhow::anyhow!(
+            "rename {} -> {}: {}",
+            e.path.display(),
+            path.display(),
+            e.error
+        )
+    })?;
+
+    #[cfg(unix)]
+    {
+        use rustix::fs::{Mode, OFlags, open};
+        let df = open(dir, OFlags::RDONLY | OFlags::DIRECTORY, Mode::empty())?;
+        rustix::fs::fdatasync(&df)?;
+    }
+    Ok(())
+}
+
+/// `index.json`: l'index entier, réécrit atomiquement
+pub struct JsonStore {
+    path: PathBuf,
+    /// Version lue sur disque (None: pas encore de fichier)
+    version: Option<u32>,
+}
+
+/// Forme écrite: la version d'abord, pour qu'un ancien binaire la voie
+#[derive(Serialize)]
+struct Document<'a> {
+    version: u32,
+    items: &'a [Entry],
+}
+
+impl Store for JsonStore {
+    fn load(&mut self) -> Result<Index> {
+        self.version = None;
+        if !self.path.exists() {
+            return Ok(Index::default());
+        }
+        let data = fs::read(&self.path).with_context(|| format!("read {}", self.path.display()))?;
+        let mut doc: serde_json::Value = serde_json::from_slice(&data)
+            .with_context(|| format!("parse {}", self.path.display()))?;
+        self.version = Some(schema::upgrade(&mut doc, &self.path)?);
+        serde_json::from_value(doc).with_context(|| format!("parse {}", self.path.display()))
+    }
+
+    fn save(&mut self, _before: &Index, after: &Index) -> Result<()> {
+        if let Some(v) = self.version.filter(|&v| v < schema::CURRENT) {
+            schema::backup(&self.path, v)?;
+        }
+        let doc = Document {
+            version: schema::CURRENT,
+            items: &after.items,
+        };
+        let buf = serde_json::to_vec_pretty(&doc).context("serialize index")?;
+        write_atomic(&self.path, &buf)?;
+        self.version = Some(schema::CURRENT);
+        Ok(())
+    }
+}
+
+/// Une ligne de `index.log`; les entrées sont identifiées par leur chemin dans le graveyard
+#[derive(Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Op {
+    Put { entry: Entry },
+    Del { trashed_path: PathBuf },
+}
+
+/// En-tête (première ligne) de `index.log`
+#[derive(Serialize)]
+struct Header {
+    version: u32,
+}
+
+/// `index.log`: `{"version":N}` puis une opération JSON par ligne, rejouées au chargement
+pub struct LogStore {
+    path: PathBuf,
+    /// Version lue sur disque (None: pas encore de fichier)
+    version: Option<u32>,
+    /// Lignes lues par `load` (pour décider de la compaction)
+    lines: usize,
+    /// Fin de la dernière ligne complète: une ligne tronquée par un crash est écrasée
+    valid_len: u64,
+}
+
+impl LogStore {
+    /// Réécrit le log avec une ligne `put` par entrée vivante
+    fn compact(&mut self, idx: &Index) -> Result<()> {
+        let mut buf = Self::header()?;
+        for e in &idx.items {
+            serde_json::to_writer(&mut buf, &Op::Put { entry: e.clone() })?;
+            buf.push(b'\n');
+        }
+        write_atomic(&self.path, &buf)?;
+        self.version = Some(schema::CURRENT);
+        self.lines = idx.items.len() + 1;
+        self.valid_len = buf.len() as u64;
+        Ok(())
+    }
+
+    fn header() -> Result<Vec<u8>> {
+        let mut buf = serde_json::to_vec(&Header {
+            version: schema::CURRENT,
+        })?;
+        buf.push(b'\n');
+        Ok(buf)
+    }
+}
+
+impl Store for LogStore {
+    fn load(&mut self) -> Result<Index> {
+        self.version = None;
+        self.lines = 0;
+        self.valid_len = 0;
+        if !self.path.exists() {
+            return Ok(Index::default());
+        }
+        let data = fs::read(&self.path).with_context(|| format!("read {}", self.path.display()))?;
+        // Rejeu sur des valeurs brutes: les migrations s'appliquent après
+        let mut version = 0;
+        let mut items: Vec<Option<serde_json::Value>> = Vec::new();
+        let mut pos: HashMap<String, usize> = HashMap::new();
+        let mut off = 0;
+        // Seules les lignes terminées par '\n' comptent
+        while let Some(n) = data[off..].iter().position(|&b| b == b'\n') {
+            let line = &data[off..off + n];
+            off += n + 1;
+            self.lines += 1;
+            if line.is_empty() {
+                continue;
+            }
+            let what = || format!("parse {} line {}", self.path.display(), self.lines);
+            let v: serde_json::Value = serde_json::from_slice(line).with_context(what)?;
+            let key = |v: &serde_json::Value| v.as_str().map(str::to_owned).with_context(what);
+            match v.get("op").and_then(|o| o.as_str()) {
+                // En-tête: vérifié avant de lire des opérations qu'on ne connaîtrait pas
+                None if self.lines == 1 => {
+                    version = schema::version_of(&v)?;
+                    schema::check(version, &self.path)?;
+                }
+                Some("put") => {
+                    let entry = v.get("entry").cloned().with_context(what)?;
+                    match pos.get(&key(&entry["trashed_path"])?) {
+                        Some(&i) => items[i] = Some(entry),
+                        None => {
+                            pos.insert(key(&entry["trashed_path"])?, items.len());
+                            items.push(Some(entry));
+                        }
+                    }
+                }
+                Some("del") => {
+                    if let Some(i) = pos.remove(&key(&v["trashed_path"])?) {
+                        items[i] = None;
+                    }
+                }
+                _ => anyhow::bail!("{}: unknown operation", what()),
+            }
+        }
+        self.valid_len = off as u64;
+        let mut doc = serde_json::json!({
+            "version": version,
+            "items": items.into_iter().flatten().collect::<Vec<_>>(),
+        });
+        self.version = Some(schema::upgrade(&mut doc, &self.path)?);
+        serde_json::from_value(doc).with_context(|| format!("parse {}", self.path.display()))
+    }
+
+    fn save(&mut self, before: &Index, after: &Index) -> Result<()> {
+        let old: HashMap<&Path, &Entry> = before
+            .items
+            .iter()
+            .map(|e| (e.trashed_path.as_path(), e))
+            .collect();
+        let kept: HashSet<&Path> = after.items.iter().map(|e| e.trashed_path.as_path()).collect();
+
+        let mut ops = Vec::new();
+        for e in &before.items {
+            if !kept.contains(e.trashed_path.as_path()) {
+                ops.push(Op::Del {
+                    trashed_path: e.trashed_path.clone(),
+                });
+            }
+        }
+        for e in &after.items {
+            if old.get(e.trashed_path.as_path()) != Some(&e) {
+                ops.push(Op::Put { entry: e.clone() });
+            }
+        }
+        // Ancien format: sauvegarde, puis instantané au format courant
+        if let Some(v) = self.version.filter(|&v| v < schema::CURRENT) {
+            schema::backup(&self.path, v)?;
+            return self.compact(after);
+        }
+        if ops.is_empty() {
+            return Ok(());
+        }
+        // Trop de lignes mortes: on repart d'un instantané
+        if self.lines + ops.len() > 2 * after.items.len() + 1024 {
+            return self.compact(after);
+        }
+
+        let mut buf = if self.valid_len == 0 {
+            self.lines += 1;
+            Self::header()?
+        } else {
+            Vec::new()
+        };
+        for op in &ops {
+            serde_json::to_writer(&mut buf, op)?;
+            buf.push(b'\n');
+        }
+        let created = !self.path.exists();
+        let mut f = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if f.metadata()?.len() > self.valid_len {
+            f.set_len(self.valid_len)?;
+        }
+        f.write_all(&buf)?;
+        f.sync_all()?;
+        if created {
+            crate::fs_safemove::fsync_dir(self.path.parent().unwrap_or(Path::new(".")))?;
+        }
+        self.lines += ops.len();
+        self.valid_len += buf.len() as u64;
+        self.version = Some(schema::CURRENT);
+        Ok(())
+    }
+}
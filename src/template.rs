@@ -0,0 +1,161 @@
+//! Gabarits de ligne pour `nrip -l` (`list.format`, `--format`):
+//! `{champ}` ou `{champ:<N}` / `{champ:>N}` / `{champ:^N}`; `{{` et `}}` pour des accolades.
+
+use anyhow::{Result, bail};
+
+/// Champs disponibles dans un gabarit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Icon,
+    Kind,
+    DeletedAt,
+    Age,
+    Basename,
+    OriginalPath,
+    TrashedPath,
+    Graveyard,
+    Size,
+    DiskUsage,
+}
+
+impl Field {
+    pub const ALL: [(&'static str, Field); 11] = [
+        ("id", Field::Id),
+        ("icon", Field::Icon),
+        ("kind", Field::Kind),
+        ("deleted_at", Field::DeletedAt),
+        ("age", Field::Age),
+        ("basename", Field::Basename),
+        ("original_path", Field::OriginalPath),
+        ("trashed_path", Field::TrashedPath),
+        ("graveyard", Field::Graveyard),
+        ("size", Field::Size),
+        ("disk_usage", Field::DiskUsage),
+    ];
+
+    fn parse(name: &str) -> Option<Field> {
+        Self::ALL.iter().find(|(n, _)| *n == name).map(|&(_, f)| f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Field {
+        field: Field,
+        align: Align,
+        width: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+impl Template {
+    pub fn parse(src: &str) -> Result<Template> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = src.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => bail!("unclosed '{{{spec}'"),
+                        }
+                    }
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(parse_placeholder(&spec)?);
+                }
+                '}' => bail!("unmatched '}}' (write '}}}}' for a literal brace)"),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Ok(Template { pieces })
+    }
+
+    /// Rend une ligne: `value` fournit le texte d'un champ, `paint` le colore une fois aligné
+    pub fn render(
+        &self,
+        value: impl Fn(Field) -> String,
+        paint: impl Fn(Field, String) -> String,
+    ) -> String {
+        let mut out = String::new();
+        for p in &self.pieces {
+            match p {
+                Piece::Text(t) => out.push_str(t),
+                &Piece::Field {
+                    field,
+                    align,
+                    width,
+                } => out.push_str(&paint(field, pad(value(field), align, width))),
+            }
+        }
+        out
+    }
+}
+
+fn parse_placeholder(spec: &str) -> Result<Piece> {
+    let (name, fmt) = spec.split_once(':').unwrap_or((spec, ""));
+    let Some(field) = Field::parse(name.trim()) else {
+        let known: Vec<&str> = Field::ALL.iter().map(|(n, _)| *n).collect();
+        bail!("unknown field '{{{name}}}' (known: {})", known.join(", "));
+    };
+    let (align, width) = match fmt.chars().next() {
+        Some('<') => (Align::Left, &fmt[1..]),
+        Some('>') => (Align::Right, &fmt[1..]),
+        Some('^') => (Align::Center, &fmt[1..]),
+        _ => (Align::Left, fmt),
+    };
+    let width = match width {
+        "" => 0,
+        w => w
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid width in '{{{spec}}}' (expected e.g. {{{name}:>9}})"))?,
+    };
+    Ok(Piece::Field {
+        field,
+        align,
+        width,
+    })
+}
+
+/// Complète à `width` caractères (jamais de troncature)
+fn pad(s: String, align: Align, width: usize) -> String {
+    let len = s.chars().count();
+    if len >= width {
+        return s;
+    }
+    let gap = width - len;
+    match align {
+        Align::Left => format!("{s}{}", " ".repeat(gap)),
+        Align::Right => format!("{}{s}", " ".repeat(gap)),
+        Align::Center => format!("{}{s}{}", " ".repeat(gap / 2), " ".repeat(gap - gap / 2)),
+    }
+}
@@ -0,0 +1,130 @@
+//! `nrip -l --tree`: entrées regroupées selon l'arborescence d'origine.
+//! Les dossiers sans entrée propre et à enfant unique sont fusionnés (`/home/u/src`).
+
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, PathBuf};
+
+use crate::index::Entry;
+
+#[derive(Default)]
+struct Node<'a> {
+    /// Enterrements de ce chemin exact (un dossier enterré peut aussi avoir des enfants)
+    here: Vec<(&'a Entry, Option<u64>)>,
+    children: BTreeMap<OsString, Node<'a>>,
+    count: usize,
+    size: u64,
+}
+
+impl<'a> Node<'a> {
+    fn insert(&mut self, parts: &[&OsStr], e: &'a Entry, size: Option<u64>) {
+        self.count += 1;
+        self.size = self.size.saturating_add(size.unwrap_or(0));
+        match parts.split_first() {
+            None => self.here.push((e, size)),
+            Some((first, rest)) => self
+                .children
+                .entry(first.to_os_string())
+                .or_default()
+                .insert(rest, e, size),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// Libellés fournis par l'appelant (couleurs, icônes)
+pub trait Labels {
+    /// Ligne d'une entrée; `name` est son nom dans l'arbre, `size` None si inconnue
+    fn entry(&self, name: &str, e: &Entry, size: Option<u64>) -> String;
+    /// En-tête de dossier: nombre d'entrées et taille cumulée (connue) du sous-arbre
+    fn dir(&self, name: &str, count: usize, size: u64) -> String;
+}
+
+/// Lignes de l'arbre, prêtes à afficher
+pub fn render(entries: &[Entry], size_of: impl Fn(&Entry) -> Option<u64>, labels: &impl Labels) -> Vec<String> {
+    let mut root = Node::default();
+    for e in entries {
+        let parts: Vec<&OsStr> = e
+            .original_path
+            .components()
+            .map(|c| match c {
+                Component::RootDir => OsStr::new("/"),
+                c => c.as_os_str(),
+            })
+            .collect();
+        root.insert(&parts, e, size_of(e));
+    }
+    let mut out = Vec::new();
+    for (name, node) in &root.children {
+        let (name, node) = collapse(PathBuf::from(name), node);
+        emit(&mut out, "", "", &name, node, labels);
+    }
+    out
+}
+
+/// Descend tant que le dossier n'est qu'un passage vers un seul sous-dossier
+fn collapse<'n, 'a>(mut name: PathBuf, mut node: &'n Node<'a>) -> (String, &'n Node<'a>) {
+    while node.here.is_empty() && node.children.len() == 1 {
+        let (child_name, child) = node.children.iter().next().unwrap();
+        if child.is_leaf() {
+            break;
+        }
+        name.push(child_name);
+        node = child;
+    }
+    (name.display().to_string(), node)
+}
+
+/// Élément affiché sous un dossier
+enum Item<'n, 'a> {
+    Entry(String, &'a Entry, Option<u64>),
+    Dir(String, &'n Node<'a>),
+}
+
+/// `lead`: préfixe de la ligne du dossier; `indent`: préfixe de ses descendants
+fn emit(out: &mut Vec<String>, lead: &str, indent: &str, name: &str, node: &Node, labels: &impl Labels) {
+    if node.is_leaf() {
+        for (e, size) in &node.here {
+            out.push(format!("{lead}{}", labels.entry(name, e, *size)));
+        }
+        return;
+    }
+    let dir_name = if name.ends_with('/') {
+        name.to_string()
+    } else {
+        format!("{name}/")
+    };
+    out.push(format!("{lead}{}", labels.dir(&dir_name, node.count, node.size)));
+
+    // Le dossier lui-même s'il a été enterré après certains de ses enfants, puis les enfants;
+    // un même chemin enterré plusieurs fois donne plusieurs lignes sœurs
+    let mut items: Vec<Item> = node
+        .here
+        .iter()
+        .map(|&(e, size)| Item::Entry(".".into(), e, size))
+        .collect();
+    for (child_name, child) in &node.children {
+        if child.is_leaf() {
+            let n = child_name.to_string_lossy();
+            items.extend(child.here.iter().map(|&(e, size)| Item::Entry(n.to_string(), e, size)));
+        } else {
+            let (n, child) = collapse(PathBuf::from(child_name), child);
+            items.push(Item::Dir(n, child));
+        }
+    }
+    let total = items.len();
+    for (i, item) in items.into_iter().enumerate() {
+        let (l, ind) = if i + 1 == total {
+            (format!("{indent}└── "), format!("{indent}    "))
+        } else {
+            (format!("{indent}├── "), format!("{indent}│   "))
+        };
+        match item {
+            Item::Entry(n, e, size) => out.push(format!("{l}{}", labels.entry(&n, e, size))),
+            Item::Dir(n, child) => emit(out, &l, &ind, &n, child, labels),
+        }
+    }
+}
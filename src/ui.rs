@@ -1,8 +1,10 @@
 use anyhow::{Context, Result};
 use chrono::{Local, TimeZone};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 use crate::index::{Index, Kind};
+use crate::size::human_size;
 use yansi::{Color, Paint};
 
 fn human_when(ts: i64) -> String {
@@ -24,7 +26,7 @@ fn kind_icon(k: Kind) -> &'static str {
 }
 
 /// Lignes pour fzf (compactes):
-/// IDX \t ICON \t DATE \t BASENAME \t ORIGINAL \t TRASHED(HIDDEN)
+/// IDX \t ICON \t DATE \t SIZE \t BASENAME \t ORIGINAL \t TRASHED(HIDDEN)
 fn build_fzf_lines(idx: &Index) -> Vec<String> {
     idx.items
         .iter()
@@ -40,12 +42,15 @@ fn build_fzf_lines(idx: &Index) -> Vec<String> {
             // Champ 0 (index) ***NON COLORÉ*** pour le parse
             let icon_p = Paint::new(icon).fg(Color::Cyan).to_string();
             let date_p = Paint::new(human_when(e.deleted_at)).dim().to_string();
+            let size = human_size(crate::size::entry_usage(e).apparent);
+            let size_p = Paint::new(format!("{size:>9}")).fg(Color::Yellow).to_string();
             let base_p = Paint::new(base).bold().to_string();
             format!(
-                "{}\t{}\t{}\t{}\t{}\t{}",
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
                 i,
                 icon_p,
                 date_p,
+                size_p,
                 base_p,
                 e.original_path.display(),
                 e.trashed_path.display()
@@ -54,34 +59,22 @@ fn build_fzf_lines(idx: &Index) -> Vec<String> {
         .collect()
 }
 
-/// Lance fzf (obligatoire). Retourne les indices sélectionnés (dans idx.items).
-pub fn pick_entries_with_fzf(idx: &Index, preview: bool) -> Result<Vec<usize>> {
-    let lines = build_fzf_lines(idx);
-    if lines.is_empty() {
-        return Ok(vec![]);
-    }
-
+/// fzf configuré comme partout dans nrip (hauteur, bordure, NUL en sortie)
+fn fzf_command() -> Command {
+    let cfg = &crate::config::get().fzf;
     let mut cmd = Command::new("fzf");
-    cmd.arg("--multi") // multi-sélection
-        .arg("--height=40%")
+    cmd.arg(format!("--height={}", cfg.height))
         .arg("--layout=reverse")
         .arg("--border")
         .arg("--ansi")
         .arg("--print0") // sortie NUL-delimitée
-        .args(["--delimiter", "\t"]) // champs = tab
-        .args(["--with-nth", "3,4,5"])
         .stdin(Stdio::piped())
         .stdout(Stdio::piped());
+    cmd
+}
 
-    if preview {
-        // Aperçu simple: liste le chemin TRASHED (colonne après "->")
-        cmd.args([
-            "--preview",
-            r#"sh -c 'printf "%s\n" "$@" | awk -F"\t" "{print \$NF}" | xargs -r ls -ld --'"#,
-            "--preview-window=right:60%",
-        ]);
-    }
-
+/// Envoie `lines` à fzf; None si l'utilisateur abandonne (Échap)
+fn run_fzf(mut cmd: Command, lines: &[String]) -> Result<Option<Vec<String>>> {
     let mut child = cmd
         .spawn()
         .context("fzf not found (please install `fzf`)")?;
@@ -89,31 +82,141 @@ pub fn pick_entries_with_fzf(idx: &Index, preview: bool) -> Result<Vec<usize>> {
     {
         use std::io::Write;
         let stdin = child.stdin.as_mut().context("open fzf stdin")?;
-        for line in &lines {
+        for line in lines {
             writeln!(stdin, "{line}")?;
         }
     }
 
     let out = child.wait_with_output().context("run fzf")?;
     if !out.status.success() {
-        return Ok(vec![]);
+        return Ok(None);
     }
+    Ok(Some(
+        out.stdout
+            .split(|&b| b == 0u8)
+            .filter_map(|s| std::str::from_utf8(s).ok())
+            .map(str::to_owned)
+            .collect(),
+    ))
+}
 
+/// Indices (dans idx.items) en tête des lignes choisies
+fn picked_indices(idx: &Index, picked: &[String]) -> Vec<usize> {
     let mut selected = Vec::new();
-    for part in out.stdout.split(|&b| b == 0u8).filter(|s| !s.is_empty()) {
-        let s = match std::str::from_utf8(part) {
-            Ok(x) => x,
-            Err(_) => continue,
-        };
-        if let Some(first_field) = s.split('\t').next() {
-            if let Ok(i) = first_field.trim().parse::<usize>() {
-                if i < idx.items.len() {
-                    selected.push(i);
-                }
-            }
+    for s in picked.iter().filter(|s| !s.is_empty()) {
+        if let Some(first_field) = s.split('\t').next()
+            && let Ok(i) = first_field.trim().parse::<usize>()
+            && i < idx.items.len()
+        {
+            selected.push(i);
         }
     }
     selected.sort_unstable();
     selected.dedup();
-    Ok(selected)
+    selected
+}
+
+/// fzf sur les entrées; `fzf.preview` s'applique toujours, `preview` n'active que l'aperçu par défaut
+fn entries_command(preview: bool) -> Command {
+    let cfg = &crate::config::get().fzf;
+    let mut cmd = fzf_command();
+    cmd.arg("--multi") // multi-sélection
+        .args(["--delimiter", "\t"]) // champs = tab
+        .args(["--with-nth", "3,4,5,6"]);
+
+    if let Some(p) = &cfg.preview {
+        // Commande utilisateur: champs fzf 6 = ORIGINAL, 7 = TRASHED
+        let p = p
+            .replace("{trashed_path}", "{7}")
+            .replace("{original_path}", "{6}");
+        cmd.args(["--preview", p.as_str(), "--preview-window=right:60%"]);
+    } else if preview {
+        // Aperçu simple: liste le chemin TRASHED (colonne après "->")
+        cmd.args([
+            "--preview",
+            r#"sh -c 'printf "%s\n" "$@" | awk -F"\t" "{print \$NF}" | xargs -r ls -ld --'"#,
+            "--preview-window=right:60%",
+        ]);
+    }
+    cmd
+}
+
+/// Lance fzf (obligatoire). Retourne les indices sélectionnés (dans idx.items).
+pub fn pick_entries_with_fzf(idx: &Index, preview: bool) -> Result<Vec<usize>> {
+    let lines = build_fzf_lines(idx);
+    if lines.is_empty() {
+        return Ok(vec![]);
+    }
+    let picked = run_fzf(entries_command(preview), &lines)?.unwrap_or_default();
+    Ok(picked_indices(idx, &picked))
+}
+
+/// Choix fait dans le sélecteur de `--resurrect`
+pub enum Pick {
+    /// Entrées entières
+    Entries(Vec<usize>),
+    /// Ctrl-O sur un dossier enterré: chemins relatifs choisis à l'intérieur
+    Inside(usize, Vec<PathBuf>),
+}
+
+/// Sélecteur de `--resurrect`: Entrée restaure, Ctrl-O ouvre un dossier enterré
+pub fn pick_for_restore(idx: &Index) -> Result<Pick> {
+    let lines = build_fzf_lines(idx);
+    if lines.is_empty() {
+        return Ok(Pick::Entries(vec![]));
+    }
+    let mut cmd = entries_command(false);
+    cmd.args(["--expect", "ctrl-o"])
+        .args(["--header", "enter: restore · ctrl-o: pick inside a buried directory"]);
+    let Some(picked) = run_fzf(cmd, &lines)? else {
+        return Ok(Pick::Entries(vec![]));
+    };
+    // --expect: la touche d'abord ("" pour Entrée), puis les lignes
+    let (key, rows) = picked.split_first().map_or(("", &[][..]), |(k, r)| (k.as_str(), r));
+    let selected = picked_indices(idx, rows);
+    match (key, selected.as_slice()) {
+        ("ctrl-o", &[i]) if idx.items[i].kind == Kind::Dir => {
+            Ok(Pick::Inside(i, pick_inside(&idx.items[i].trashed_path)?))
+        }
+        ("ctrl-o", _) => anyhow::bail!("ctrl-o: pick a single buried directory"),
+        _ => Ok(Pick::Entries(selected)),
+    }
+}
+
+/// Second fzf sur le contenu d'un dossier enterré (chemins relatifs)
+fn pick_inside(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut lines = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+    while let Some(rel) = stack.pop() {
+        let Ok(rd) = std::fs::read_dir(dir.join(&rel)) else { continue };
+        let mut names: Vec<_> = rd.filter_map(|d| d.ok()).collect();
+        names.sort_by_key(|d| d.file_name());
+        for d in names.into_iter().rev() {
+            let child = rel.join(d.file_name());
+            if d.file_type().is_ok_and(|t| t.is_dir()) {
+                stack.push(child.clone());
+                lines.push(format!("{}/", child.display()));
+            } else {
+                lines.push(child.display().to_string());
+            }
+        }
+    }
+    lines.sort();
+    let mut cmd = fzf_command();
+    cmd.arg("--multi");
+    let picked = run_fzf(cmd, &lines)?.unwrap_or_default();
+    let mut rels: Vec<PathBuf> = picked
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| PathBuf::from(s.trim_end_matches('/')))
+        .collect();
+    // `dir/` et `dir/file`: le dossier emporte déjà le fichier (tri: parents d'abord)
+    rels.sort();
+    let mut outer: Vec<PathBuf> = Vec::with_capacity(rels.len());
+    for r in rels {
+        if !outer.iter().any(|o| r.starts_with(o)) {
+            outer.push(r);
+        }
+    }
+    Ok(outer)
 }
@@ -0,0 +1,123 @@
+//! Moteur de copie du repli EXDEV.
+//! Benchmark: `cargo test --release --test bench_copy -- --ignored --nocapture`
+
+use assert_fs::prelude::*;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Contenu pseudo-aléatoire déterministe (pas de motif trivial)
+fn write_pattern(p: &Path, len: usize) {
+    let mut f = std::fs::File::create(p).unwrap();
+    let mut x: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut buf = vec![0u8; 1 << 20];
+    let mut left = len;
+    while left > 0 {
+        for b in buf.iter_mut() {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            *b = x as u8;
+        }
+        let n = left.min(buf.len());
+        f.write_all(&buf[..n]).unwrap();
+        left -= n;
+    }
+    f.sync_all().unwrap();
+}
+
+fn same_content(a: &Path, b: &Path) -> bool {
+    let (mut fa, mut fb) = (
+        std::fs::File::open(a).unwrap(),
+        std::fs::File::open(b).unwrap(),
+    );
+    let (mut ba, mut bb) = (vec![0u8; 1 << 20], vec![0u8; 1 << 20]);
+    loop {
+        let na = fa.read(&mut ba).unwrap();
+        let nb = fb.read_exact(&mut bb[..na]).map(|_| na).unwrap_or(usize::MAX);
+        if na != nb || ba[..na] != bb[..na] {
+            return false;
+        }
+        if na == 0 {
+            return fb.read(&mut bb).unwrap() == 0;
+        }
+    }
+}
+
+/// Cibles: même FS, et un autre FS (tmpfs) si disponible
+fn destinations(tmp: &Path) -> Vec<(String, PathBuf, Option<tempfile::TempDir>)> {
+    let mut out = vec![("same fs".to_string(), tmp.to_path_buf(), None)];
+    if let Ok(shm) = tempfile::tempdir_in("/dev/shm") {
+        let p = shm.path().to_path_buf();
+        out.push(("cross fs".to_string(), p, Some(shm)));
+    }
+    out
+}
+
+/// Ancien chemin: flux bufferisé 8 KiB en userspace + fsync
+fn buffered_copy(src: &Path, dst: &Path) {
+    let mut i = std::fs::File::open(src).unwrap();
+    let mut o = std::fs::File::create(dst).unwrap();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = i.read(&mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        o.write_all(&buf[..n]).unwrap();
+    }
+    o.sync_all().unwrap();
+}
+
+#[test]
+fn large_files_are_copied_intact() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let src = tmp.child("big.bin");
+    write_pattern(src.path(), 24 * 1024 * 1024 + 123);
+
+    for (label, dir, _keep) in destinations(tmp.path()) {
+        let dst = dir.join("copy.bin");
+        nrip::fs_safemove::copy_recursively(src.path(), &dst)?;
+        assert!(same_content(src.path(), &dst), "{label}: content differs");
+        std::fs::remove_file(&dst)?;
+    }
+    Ok(())
+}
+
+#[test]
+#[ignore = "benchmark: run with --ignored --nocapture"]
+fn bench_accelerated_copy_vs_buffered() {
+    const SIZE: usize = 512 * 1024 * 1024;
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let src = tmp.child("bench.bin");
+    write_pattern(src.path(), SIZE);
+
+    let mib = SIZE as f64 / (1024.0 * 1024.0);
+    let rate = |d: Duration| mib / d.as_secs_f64();
+    for (label, dir, _keep) in destinations(tmp.path()) {
+        let old = dir.join("buffered.bin");
+        let t = Instant::now();
+        buffered_copy(src.path(), &old);
+        let t_old = t.elapsed();
+        std::fs::remove_file(&old).unwrap();
+
+        let new = dir.join("engine.bin");
+        let t = Instant::now();
+        nrip::fs_safemove::copy_recursively(src.path(), &new).unwrap();
+        let t_new = t.elapsed();
+        assert!(same_content(src.path(), &new));
+        std::fs::remove_file(&new).unwrap();
+
+        println!(
+            "{label:>8}: buffered {:>8.1} MiB/s ({t_old:.2?})  engine {:>8.1} MiB/s ({t_new:.2?})  x{:.2}",
+            rate(t_old),
+            rate(t_new),
+            t_old.as_secs_f64() / t_new.as_secs_f64()
+        );
+        // Jamais nettement plus lent que l'ancien chemin
+        assert!(
+            t_new.as_secs_f64() <= t_old.as_secs_f64() * 1.25,
+            "{label}: engine slower than buffered copy"
+        );
+    }
+}
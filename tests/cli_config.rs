@@ -0,0 +1,177 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+fn isolate(tmp: &assert_fs::TempDir) {
+    util::isolate(tmp);
+    util::remove_var("NRIP_CONFIG");
+    util::remove_var("NRIP_GRAVEYARD_DIR");
+    util::remove_var("NRIP_COLOR");
+}
+
+#[test]
+#[serial]
+fn config_graveyard_dir_is_used_for_bury() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate(&tmp);
+
+    let custom = tmp.child("custom-gy");
+    tmp.child(".xdg/config/nrip/config.toml").write_str(&format!(
+        "graveyard_dir = {:?}\n",
+        custom.path().to_str().unwrap()
+    ))?;
+
+    let file = tmp.child("cfg.txt");
+    file.write_str("x")?;
+    Command::cargo_bin("nrip")?.arg(file.path()).assert().success();
+
+    file.assert(predicate::path::missing());
+    let buried: Vec<_> = std::fs::read_dir(custom.path())?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().ends_with("__cfg.txt"))
+        .collect();
+    assert_eq!(buried.len(), 1, "file should be buried in graveyard_dir");
+    tmp.child(".xdg/data/nrip/graveyard")
+        .assert(predicate::path::missing());
+
+    // et la restauration le retrouve
+    Command::cargo_bin("nrip")?
+        .args(["-r", "cfg.txt", "-y"])
+        .assert()
+        .success();
+    file.assert(predicate::path::exists());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn env_and_cli_override_config_file() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate(&tmp);
+
+    let from_file = tmp.child("from-file");
+    let from_env = tmp.child("from-env");
+    let from_cli = tmp.child("from-cli");
+    tmp.child(".xdg/config/nrip/config.toml").write_str(&format!(
+        "graveyard_dir = {:?}\n",
+        from_file.path().to_str().unwrap()
+    ))?;
+
+    let a = tmp.child("a.txt");
+    a.write_str("a")?;
+    Command::cargo_bin("nrip")?
+        .env("NRIP_GRAVEYARD_DIR", from_env.path())
+        .arg(a.path())
+        .assert()
+        .success();
+    from_env.assert(predicate::path::is_dir());
+    from_file.assert(predicate::path::missing());
+
+    let b = tmp.child("b.txt");
+    b.write_str("b")?;
+    Command::cargo_bin("nrip")?
+        .env("NRIP_GRAVEYARD_DIR", from_env.path())
+        .arg("--graveyard-dir")
+        .arg(from_cli.path())
+        .arg(b.path())
+        .assert()
+        .success();
+    from_cli.assert(predicate::path::is_dir());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn invalid_config_reports_offending_key() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate(&tmp);
+
+    let cfg = tmp.child(".xdg/config/nrip/config.toml");
+
+    cfg.write_str("[fzf]\nheight = \"tall\"\n")?;
+    Command::cargo_bin("nrip")?
+        .arg("--list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("fzf.height"));
+
+    cfg.write_str("[list]\ntime_fmt = \"%Y\"\n")?;
+    Command::cargo_bin("nrip")?
+        .arg("--list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("time_fmt"));
+
+    cfg.write_str("graveyard_dir = \"relative/dir\"\n")?;
+    Command::cargo_bin("nrip")?
+        .arg("--list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("graveyard_dir"));
+
+    Command::cargo_bin("nrip")?
+        .arg("--config")
+        .arg(tmp.child("missing.toml").path())
+        .arg("--list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("config file not found"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn list_time_format_is_configurable() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate(&tmp);
+
+    tmp.child(".xdg/config/nrip/config.toml")
+        .write_str("color = \"never\"\n[list]\ntime_format = \"year=%Y\"\n")?;
+
+    let file = tmp.child("fmt.txt");
+    file.write_str("x")?;
+    Command::cargo_bin("nrip")?.arg(file.path()).assert().success();
+
+    Command::cargo_bin("nrip")?
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(year=").and(predicate::str::contains("fmt.txt")));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn list_format_is_configurable_and_validated() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate(&tmp);
+    let cfg = tmp.child(".xdg/config/nrip/config.toml");
+    cfg.write_str("color = \"never\"\n[list]\nformat = \"{kind}:{basename}:{age}\"\n")?;
+
+    let file = tmp.child("fmt.txt");
+    file.write_str("x")?;
+    Command::cargo_bin("nrip")?.arg(file.path()).assert().success();
+    Command::cargo_bin("nrip")?
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("F:fmt.txt:"));
+
+    for (format, err) in [
+        ("{size:>x}", "invalid width in '{size:>x}'"),
+        ("{id", "unclosed '{id'"),
+        ("id}", "unmatched '}'"),
+    ] {
+        cfg.write_str(&format!("[list]\nformat = \"{format}\"\n"))?;
+        Command::cargo_bin("nrip")?
+            .arg("--list")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("`list.format`").and(predicate::str::contains(err)));
+    }
+    Ok(())
+}
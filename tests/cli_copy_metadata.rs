@@ -0,0 +1,147 @@
+#![cfg(unix)]
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use rustix::fs::{AtFlags, CWD, Timespec, Timestamps, XattrFlags, lgetxattr, lsetxattr, utimensat};
+use serial_test::serial;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::process::Command;
+
+mod util;
+
+const MTIME: i64 = 1_500_000_000;
+
+/// Fige mode, dates, xattr (et propriétaire si root) sur `p`
+fn stamp(p: &Path, mode: u32) {
+    std::fs::set_permissions(p, std::fs::Permissions::from_mode(mode)).unwrap();
+    let _ = lsetxattr(p, "user.nrip", b"kept", XattrFlags::empty());
+    if rustix::process::geteuid().is_root() {
+        std::os::unix::fs::lchown(p, Some(1234), Some(4321)).unwrap();
+    }
+    set_times(p);
+}
+
+fn set_times(p: &Path) {
+    let t = Timestamps {
+        last_access: Timespec {
+            tv_sec: MTIME - 10,
+            tv_nsec: 0,
+        },
+        last_modification: Timespec {
+            tv_sec: MTIME,
+            tv_nsec: 123_000_000,
+        },
+    };
+    utimensat(CWD, p, &t, AtFlags::SYMLINK_NOFOLLOW).unwrap();
+}
+
+fn xattr(p: &Path) -> Option<Vec<u8>> {
+    let mut buf = [0u8; 64];
+    let n = lgetxattr(p, "user.nrip", &mut buf[..]).ok()?;
+    Some(buf[..n].to_vec())
+}
+
+fn assert_same_metadata(a: &Path, b: &Path) {
+    let (ma, mb) = (
+        std::fs::symlink_metadata(a).unwrap(),
+        std::fs::symlink_metadata(b).unwrap(),
+    );
+    assert_eq!(ma.mode(), mb.mode(), "mode of {}", b.display());
+    assert_eq!(ma.uid(), mb.uid(), "uid of {}", b.display());
+    assert_eq!(ma.gid(), mb.gid(), "gid of {}", b.display());
+    assert_eq!(ma.mtime(), mb.mtime(), "mtime of {}", b.display());
+    assert_eq!(ma.mtime_nsec(), mb.mtime_nsec(), "mtime_nsec of {}", b.display());
+    // Lire la source bouge son atime: la copie doit garder la date figée.
+    // Les dossiers sont parcourus (mesure de taille) avant la copie: pas de vérification.
+    if !mb.is_dir() {
+        assert_eq!(mb.atime(), MTIME - 10, "atime of {}", b.display());
+    }
+    assert_eq!(xattr(a), xattr(b), "xattr of {}", b.display());
+}
+
+/// Arbre de test: fichier exécutable, dossier 0750, fichier en lecture seule, symlink
+fn build_tree(root: &Path) {
+    std::fs::create_dir_all(root.join("sub")).unwrap();
+    std::fs::write(root.join("run.sh"), "#!/bin/sh\n").unwrap();
+    std::fs::write(root.join("sub/ro.txt"), "read only").unwrap();
+    std::os::unix::fs::symlink("sub/ro.txt", root.join("link")).unwrap();
+    stamp(&root.join("run.sh"), 0o751);
+    stamp(&root.join("sub/ro.txt"), 0o440);
+    set_times(&root.join("link"));
+    stamp(&root.join("sub"), 0o750);
+    stamp(root, 0o755);
+}
+
+fn assert_same_tree(a: &Path, b: &Path) {
+    for rel in ["", "run.sh", "sub", "sub/ro.txt", "link"] {
+        assert_same_metadata(&a.join(rel), &b.join(rel));
+    }
+}
+
+/// À appeler en dernier: readlink bouge l'atime du lien
+fn assert_link_target(root: &Path) {
+    assert_eq!(
+        std::fs::read_link(root.join("link")).unwrap(),
+        Path::new("sub/ro.txt")
+    );
+}
+
+#[test]
+fn copy_recursively_preserves_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let src = tmp.child("src");
+    build_tree(src.path());
+
+    let dst = tmp.child("dst");
+    nrip::fs_safemove::copy_recursively(src.path(), dst.path())?;
+    assert_same_tree(src.path(), dst.path());
+    assert_link_target(dst.path());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn cross_device_round_trip_keeps_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let shm = Path::new("/dev/shm");
+    if std::fs::metadata(shm).map(|m| m.dev()).ok() == Some(std::fs::metadata(tmp.path())?.dev())
+        || !shm.is_dir()
+    {
+        eprintln!("skip: no second filesystem available");
+        return Ok(());
+    }
+    let other = tempfile::tempdir_in(shm)?;
+
+    util::set_var("HOME", tmp.path());
+    util::set_var("XDG_DATA_HOME", tmp.child(".xdg/data").path());
+    util::set_var("XDG_CONFIG_HOME", tmp.child(".xdg/config").path());
+    // Graveyard principal obligatoire: chaque trajet passe par le repli EXDEV
+    tmp.child(".xdg/config/nrip/config.toml")
+        .write_str("per_mount_graveyards = false\n")?;
+
+    let tree = other.path().join("tree");
+    build_tree(&tree);
+    let reference = tmp.child("reference");
+    build_tree(reference.path());
+
+    Command::cargo_bin("nrip")?.arg(&tree).assert().success();
+    assert!(!tree.exists());
+
+    // Dans le graveyard (autre FS): mêmes métadonnées
+    let gy = tmp.child(".xdg/data/nrip/graveyard");
+    let buried = std::fs::read_dir(gy.path())?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|p| p.to_string_lossy().ends_with("__tree"))
+        .expect("buried tree");
+    assert_same_tree(reference.path(), &buried);
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "tree", "-y"])
+        .assert()
+        .success();
+    assert_same_tree(reference.path(), &tree);
+    assert_link_target(&tree);
+    Ok(())
+}
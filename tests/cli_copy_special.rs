@@ -0,0 +1,144 @@
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use rustix::fs::{CWD, FileType, Mode, mknodat};
+use serial_test::serial;
+use std::io::{Seek, SeekFrom, Write};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::Path;
+use std::time::Duration;
+
+mod util;
+
+fn mkfifo(p: &Path) {
+    mknodat(CWD, p, FileType::Fifo, Mode::from_raw_mode(0o640), 0).unwrap();
+}
+
+#[test]
+fn hardlinks_inside_the_tree_stay_linked() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let src = tmp.child("src");
+    src.child("a.bin").write_binary(&[1u8; 4096])?;
+    std::fs::hard_link(src.child("a.bin").path(), src.child("b.bin").path())?;
+    // Lien vers l'extérieur de l'arbre: copie indépendante
+    src.child("solo.txt").write_str("solo")?;
+    std::fs::hard_link(src.child("solo.txt").path(), tmp.child("outside.txt").path())?;
+
+    let dst = tmp.child("dst");
+    nrip::fs_safemove::copy_recursively(src.path(), dst.path())?;
+
+    let a = std::fs::metadata(dst.child("a.bin").path())?;
+    let b = std::fs::metadata(dst.child("b.bin").path())?;
+    assert_eq!(a.ino(), b.ino());
+    assert_eq!(a.nlink(), 2);
+    assert_eq!(std::fs::metadata(dst.child("solo.txt").path())?.nlink(), 1);
+    dst.child("solo.txt").assert("solo");
+    Ok(())
+}
+
+#[test]
+fn sparse_files_keep_their_holes() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let src = tmp.child("disk.img");
+    let len: u64 = 64 * 1024 * 1024;
+    {
+        let mut f = std::fs::File::create(src.path())?;
+        f.set_len(len)?;
+        f.seek(SeekFrom::Start(32 * 1024 * 1024))?;
+        f.write_all(b"needle")?;
+    }
+    let src_md = std::fs::metadata(src.path())?;
+    if src_md.blocks() * 512 >= len {
+        eprintln!("skip: filesystem does not support sparse files");
+        return Ok(());
+    }
+
+    let dst = tmp.child("copy.img");
+    nrip::fs_safemove::copy_recursively(src.path(), dst.path())?;
+
+    let md = std::fs::metadata(dst.path())?;
+    assert_eq!(md.len(), len);
+    assert!(
+        md.blocks() * 512 < 1024 * 1024,
+        "copy allocated {} bytes",
+        md.blocks() * 512
+    );
+    let data = std::fs::read(dst.path())?;
+    assert_eq!(&data[32 * 1024 * 1024..32 * 1024 * 1024 + 6], b"needle");
+    assert!(data[..32 * 1024 * 1024].iter().all(|&b| b == 0));
+    Ok(())
+}
+
+#[test]
+fn special_files_are_recreated_not_read() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let src = tmp.child("src");
+    src.create_dir_all()?;
+    mkfifo(&src.path().join("pipe"));
+    // /dev/null bis (1:3), seulement avec CAP_MKNOD
+    let has_dev = mknodat(
+        CWD,
+        src.path().join("null"),
+        FileType::CharacterDevice,
+        Mode::from_raw_mode(0o666),
+        rustix::fs::makedev(1, 3),
+    )
+    .is_ok();
+
+    let dst = tmp.child("dst");
+    nrip::fs_safemove::copy_recursively(src.path(), dst.path())?;
+
+    let pipe = std::fs::symlink_metadata(dst.child("pipe").path())?;
+    assert!(pipe.file_type().is_fifo());
+    assert_eq!(pipe.mode() & 0o777, 0o640);
+    if has_dev {
+        let null = std::fs::symlink_metadata(dst.child("null").path())?;
+        assert!(null.file_type().is_char_device());
+        assert_eq!(null.rdev(), rustix::fs::makedev(1, 3));
+    }
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn cross_device_bury_of_tree_with_fifo_does_not_hang() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let shm = Path::new("/dev/shm");
+    if !shm.is_dir() || std::fs::metadata(shm)?.dev() == std::fs::metadata(tmp.path())?.dev() {
+        eprintln!("skip: no second filesystem available");
+        return Ok(());
+    }
+    let other = tempfile::tempdir_in(shm)?;
+
+    util::set_var("HOME", tmp.path());
+    util::set_var("XDG_DATA_HOME", tmp.child(".xdg/data").path());
+    util::set_var("XDG_CONFIG_HOME", tmp.child(".xdg/config").path());
+    tmp.child(".xdg/config/nrip/config.toml")
+        .write_str("per_mount_graveyards = false\n")?;
+
+    let tree = other.path().join("project");
+    std::fs::create_dir_all(&tree)?;
+    std::fs::write(tree.join("data"), "d")?;
+    std::fs::hard_link(tree.join("data"), tree.join("data.link"))?;
+    mkfifo(&tree.join("pipe"));
+
+    Command::cargo_bin("nrip")?
+        .arg(&tree)
+        .timeout(Duration::from_secs(20))
+        .assert()
+        .success();
+    assert!(!tree.exists());
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "project", "-y"])
+        .timeout(Duration::from_secs(20))
+        .assert()
+        .success();
+    assert!(std::fs::symlink_metadata(tree.join("pipe"))?.file_type().is_fifo());
+    assert_eq!(
+        std::fs::metadata(tree.join("data"))?.ino(),
+        std::fs::metadata(tree.join("data.link"))?.ino()
+    );
+    Ok(())
+}
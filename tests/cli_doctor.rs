@@ -0,0 +1,161 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::io::Write;
+use std::process::Command;
+
+mod util;
+
+#[test]
+#[serial]
+fn doctor_reports_then_fixes_inconsistencies() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let gy = tmp.child(".xdg/data/nrip/graveyard");
+
+    let healthy = tmp.child("healthy.txt");
+    let gone = tmp.child("gone.txt");
+    healthy.write_str("h")?;
+    gone.write_str("g")?;
+    Command::cargo_bin("nrip")?
+        .args([healthy.path(), gone.path()])
+        .assert()
+        .success();
+    Command::cargo_bin("nrip")?
+        .arg("--doctor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Graveyard is healthy."));
+
+    // Entrée dont le fichier a disparu
+    let v: serde_json::Value = serde_json::from_str(&util::index_str(&tmp))?;
+    let gone_trashed = v["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["original_path"].as_str().unwrap().ends_with("gone.txt"))
+        .unwrap()["trashed_path"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    std::fs::remove_file(&gone_trashed)?;
+
+    // Orphelin connu du journal (COMMIT déjà passé: la récupération ne le voit pas)
+    let orphan = gy.child("20260102T030405__abcdefgh__orphan.txt");
+    orphan.write_str("o")?;
+    let orphan_orig = tmp.child("docs/orphan.txt");
+    let mut j = std::fs::OpenOptions::new()
+        .append(true)
+        .open(gy.child(".journal").path())?;
+    writeln!(
+        j,
+        "DONE\t{}\t{}\nCOMMIT",
+        orphan_orig.path().display(),
+        orphan.path().display()
+    )?;
+
+    // Copie inter-device abandonnée
+    gy.child("20260102T030405__zzzzzzzz__big.copying/part")
+        .write_str("p")?;
+
+    Command::cargo_bin("nrip")?
+        .arg("--doctor")
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains("orphan")
+                .and(predicate::str::contains("orphan.txt"))
+                .and(predicate::str::contains("dangling"))
+                .and(predicate::str::contains("gone.txt"))
+                .and(predicate::str::contains("stale-temp")),
+        )
+        .stderr(predicate::str::contains("3 issue(s) found"));
+
+    Command::cargo_bin("nrip")?
+        .args(["--doctor", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fixed 3 of 3 issue(s)."));
+
+    let idx = util::index_str(&tmp);
+    assert!(idx.contains("orphan.txt") && idx.contains("healthy.txt"));
+    assert!(!idx.contains("gone.txt"));
+    gy.child("20260102T030405__zzzzzzzz__big.copying")
+        .assert(predicate::path::missing());
+
+    // L'orphelin adopté se restaure à son emplacement d'origine
+    Command::cargo_bin("nrip")?
+        .args(["-r", "orphan", "-y"])
+        .assert()
+        .success();
+    orphan_orig.assert("o");
+
+    Command::cargo_bin("nrip")?
+        .arg("--doctor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Graveyard is healthy."));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn doctor_leaves_unknown_orphans_alone() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    let stray = tmp.child(".xdg/data/nrip/graveyard/dropped-by-hand");
+    stray.write_str("?")?;
+
+    Command::cargo_bin("nrip")?
+        .args(["--doctor", "--fix"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("original path unknown"))
+        .stderr(predicate::str::contains("1 issue(s) need manual attention"));
+    stray.assert("?");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn doctor_without_fix_writes_nothing() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let gy = tmp.child(".xdg/data/nrip/graveyard");
+    let f = tmp.child("kept.txt");
+    f.write_str("k")?;
+    Command::cargo_bin("nrip")?.arg(f.path()).assert().success();
+
+    // Enterrement interrompu: déplacé, jamais indexé
+    let lost = gy.child("20260101T000000__abcdefgh__lost.txt");
+    lost.write_str("l")?;
+    let mut j = std::fs::OpenOptions::new()
+        .append(true)
+        .open(gy.child(".journal").path())?;
+    writeln!(
+        j,
+        "PENDING\t{}\t{}",
+        tmp.child("lost.txt").path().display(),
+        lost.path().display()
+    )?;
+    let journal_before = std::fs::read_to_string(gy.child(".journal").path())?;
+    let index_before = util::index_str(&tmp);
+
+    let out = Command::cargo_bin("nrip")?
+        .args(["--doctor", "--json"])
+        .output()?;
+    assert!(!out.status.success());
+    let doc: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(doc["command"], "doctor");
+    let results = doc["results"].as_array().unwrap();
+    let issues: Vec<&str> = results.iter().map(|r| r["issue"].as_str().unwrap()).collect();
+    assert_eq!(issues, ["interrupted", "orphan"]);
+    assert!(results.iter().all(|r| r["action"] == "issue" && r["fixed"] == false));
+    assert_eq!(results[1]["original_path"], tmp.child("lost.txt").path().to_str().unwrap());
+
+    assert_eq!(std::fs::read_to_string(gy.child(".journal").path())?, journal_before);
+    assert_eq!(util::index_str(&tmp), index_before);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+fn isolate_freedesktop(tmp: &assert_fs::TempDir) {
+    util::isolate_with_config(tmp, "storage = \"freedesktop\"\ncolor = \"never\"\n");
+}
+
+#[test]
+#[serial]
+fn bury_writes_spec_compliant_trashinfo() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate_freedesktop(&tmp);
+
+    let file = tmp.child("with space%.txt");
+    file.write_str("x")?;
+    Command::cargo_bin("nrip")?.arg(file.path()).assert().success();
+    file.assert(predicate::path::missing());
+
+    let trash = tmp.child(".xdg/data/Trash");
+    let infos: Vec<_> = std::fs::read_dir(trash.child("info").path())?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .collect();
+    assert_eq!(infos.len(), 1);
+    let info = std::fs::read_to_string(&infos[0])?;
+    assert!(info.starts_with("[Trash Info]\n"), "{info}");
+    assert!(info.contains("with%20space%25.txt"), "{info}");
+    assert!(info.contains("DeletionDate="), "{info}");
+
+    // files/<name> ↔ info/<name>.trashinfo
+    let name = infos[0].file_stem().unwrap();
+    trash.child("files").child(name).assert(predicate::path::exists());
+
+    // resurrect retire aussi le .trashinfo
+    Command::cargo_bin("nrip")?
+        .args(["-r", "with space", "-y"])
+        .assert()
+        .success();
+    file.assert(predicate::path::exists());
+    assert!(!infos[0].exists());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn items_trashed_by_other_tools_are_manageable() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate_freedesktop(&tmp);
+
+    // Mis à la corbeille par un gestionnaire de fichiers
+    let trash = tmp.child(".xdg/data/Trash");
+    trash.child("files/report.txt").write_str("report")?;
+    trash.child("files/old notes").create_dir_all()?;
+    let report_orig = tmp.child("docs/report.txt");
+    let notes_orig = tmp.child("old notes");
+    trash.child("info/report.txt.trashinfo").write_str(&format!(
+        "[Trash Info]\nPath={}\nDeletionDate=2024-01-02T03:04:05\n",
+        report_orig.path().display()
+    ))?;
+    trash.child("info/old notes.trashinfo").write_str(&format!(
+        "[Trash Info]\nPath={}\nDeletionDate=2024-01-03T00:00:00\n",
+        notes_orig.path().display().to_string().replace(' ', "%20")
+    ))?;
+
+    Command::cargo_bin("nrip")?
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("report.txt")
+                .and(predicate::str::contains("old notes"))
+                .and(predicate::str::contains("2024-01-02")),
+        );
+
+    // resurrect crée le parent manquant
+    Command::cargo_bin("nrip")?
+        .args(["-r", "report", "-y"])
+        .assert()
+        .success();
+    report_orig.assert("report");
+    trash
+        .child("info/report.txt.trashinfo")
+        .assert(predicate::path::missing());
+
+    Command::cargo_bin("nrip")?
+        .args(["-c", "old notes", "-y"])
+        .assert()
+        .success();
+    trash.child("files/old notes").assert(predicate::path::missing());
+    trash
+        .child("info/old notes.trashinfo")
+        .assert(predicate::path::missing());
+    notes_orig.assert(predicate::path::missing());
+    Ok(())
+}
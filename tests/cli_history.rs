@@ -0,0 +1,232 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serde_json::Value;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+/// Trois versions de notes.txt, enterrées il y a 3, 2 et 1 jour(s); "current" en place
+fn three_versions(tmp: &assert_fs::TempDir) -> assert_fs::fixture::ChildPath {
+    let notes = tmp.child("notes.txt");
+    for v in ["one\n", "two\n", "three\n"] {
+        notes.write_str(v).unwrap();
+        util::bury(notes.path());
+    }
+    notes.write_str("current\n").unwrap();
+
+    let index = tmp.child(".xdg/data/nrip/index.json");
+    let mut doc: Value = serde_json::from_str(&std::fs::read_to_string(index.path()).unwrap()).unwrap();
+    let now = chrono::Utc::now().timestamp();
+    for (i, e) in doc["items"].as_array_mut().unwrap().iter_mut().enumerate() {
+        e["deleted_at"] = (now - (3 - i as i64) * 86400).into();
+    }
+    index.write_str(&doc.to_string()).unwrap();
+    notes
+}
+
+#[test]
+#[serial]
+fn history_lists_generations_newest_first() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let notes = three_versions(&tmp);
+
+    Command::cargo_bin("nrip")?
+        .arg("--history")
+        .arg(notes.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("3 buried version(s), newest first")
+                .and(predicate::str::contains("@0  current"))
+                .and(predicate::str::is_match(r"@1 .*1d ago")?)
+                .and(predicate::str::is_match(r"@3 .*3d ago")?),
+        );
+
+    let out = Command::cargo_bin("nrip")?
+        .args(["--json", "--history", "notes.txt"])
+        .output()?;
+    let doc: Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(doc["command"], "history");
+    let items = doc["items"].as_array().unwrap();
+    let contents: Vec<(u64, String)> = items
+        .iter()
+        .map(|e| {
+            let text = std::fs::read_to_string(e["trashed_path"].as_str().unwrap()).unwrap();
+            (e["generation"].as_u64().unwrap(), text)
+        })
+        .collect();
+    assert_eq!(
+        contents,
+        [(1, "three\n".into()), (2, "two\n".into()), (3, "one\n".into())]
+    );
+
+    // Plusieurs chemins du même nom: il faut le chemin complet
+    tmp.child("sub/notes.txt").write_str("other")?;
+    util::bury(tmp.child("sub/notes.txt").path());
+    Command::cargo_bin("nrip")?
+        .args(["--history", "notes.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("matches several paths; give the full path"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn pick_a_version_by_generation_or_time() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let notes = three_versions(&tmp);
+
+    // Sans précision: la dernière, plus d'ambiguïté
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes"])
+        .assert()
+        .success()
+        .stdout("three\n")
+        .stderr(predicate::str::contains("taking the latest"));
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes", "--generation", "3"])
+        .assert()
+        .success()
+        .stdout("one\n");
+    // En place il y a 36h: celle enterrée il y a 1 jour
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes", "--as-of", "36h"])
+        .assert()
+        .success()
+        .stdout("three\n");
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes", "--as-of", "60h"])
+        .assert()
+        .success()
+        .stdout("two\n");
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes", "--as-of", "2h"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("the version as of then is the current one"));
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes", "--generation", "4"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has 3 buried version(s), not 4"));
+
+    let old = tmp.child("notes.v2.txt");
+    Command::cargo_bin("nrip")?
+        .args(["-r", "notes", "--generation", "2", "-y", "--to"])
+        .arg(old.path())
+        .assert()
+        .success();
+    old.assert("two\n");
+    notes.assert("current\n");
+    let out = Command::cargo_bin("nrip")?
+        .args(["--json", "--history", "notes"])
+        .output()?;
+    let doc: Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(doc["items"].as_array().unwrap().len(), 2);
+
+    Command::cargo_bin("nrip")?
+        .args(["--generation", "1"])
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn diff_compares_generations() -> Result<(), Box<dyn std::error::Error>> {
+    if Command::new("diff").arg("--version").output().is_err() {
+        eprintln!("skipped: no diff");
+        return Ok(());
+    }
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let notes = three_versions(&tmp);
+
+    Command::cargo_bin("nrip")?
+        .arg("--history")
+        .arg(notes.path())
+        .args(["--diff", "1"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("notes.txt (current)")
+                .and(predicate::str::contains("-three"))
+                .and(predicate::str::contains("+current")),
+        );
+    Command::cargo_bin("nrip")?
+        .args(["--history", "notes", "--diff", "3:2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-one").and(predicate::str::contains("+two")));
+    Command::cargo_bin("nrip")?
+        .args(["--history", "notes", "--diff", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected GEN or GEN:GEN"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn yes_restores_the_latest_version_only() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let notes = three_versions(&tmp);
+    std::fs::remove_file(notes.path())?;
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "notes", "-y"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 item(s)."));
+    notes.assert("three\n");
+
+    // Toutes les générations, sur demande, de la plus ancienne à la plus récente
+    std::fs::remove_file(notes.path())?;
+    Command::cargo_bin("nrip")?
+        .args(["-r", "notes", "-y", "--all-generations", "--on-conflict", "rename"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 2 item(s)."));
+    notes.assert("one\n");
+    tmp.child("notes (restored 2).txt").assert("two\n");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn parent_is_included_in_its_latest_version() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let proj = tmp.child("proj");
+    proj.child("v.txt").write_str("new")?;
+    proj.child("a.txt").write_str("a")?;
+    util::bury(proj.child("a.txt").path());
+    util::bury(proj.path());
+
+    // Une version plus ancienne de proj, placée en fin d'index
+    let old = tmp.child(".xdg/data/nrip/graveyard/20200101T000000__oldoldol__proj");
+    old.child("v.txt").write_str("old")?;
+    let index = tmp.child(".xdg/data/nrip/index.json");
+    let mut doc: Value = serde_json::from_str(&std::fs::read_to_string(index.path())?)?;
+    let items = doc["items"].as_array_mut().unwrap();
+    let mut older = items[1].clone();
+    older["trashed_path"] = old.path().to_str().unwrap().into();
+    older["deleted_at"] = 1_577_836_800.into();
+    items.push(older);
+    index.write_str(&doc.to_string())?;
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "a.txt", "-y"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Including 1 parent path(s)"));
+    proj.child("v.txt").assert("new");
+    proj.child("a.txt").assert("a");
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::io::Write;
+use std::process::Command;
+
+mod util;
+
+fn set_backend(tmp: &assert_fs::TempDir, backend: &str) {
+    tmp.child(".xdg/config/nrip/config.toml")
+        .write_str(&format!("color = \"never\"\n[index]\nbackend = \"{backend}\"\n"))
+        .unwrap();
+}
+
+fn bury(tmp: &assert_fs::TempDir, name: &str) {
+    let f = tmp.child(name);
+    f.write_str(name).unwrap();
+    Command::cargo_bin("nrip")
+        .unwrap()
+        .arg(f.path())
+        .assert()
+        .success();
+}
+
+#[test]
+#[serial]
+fn json_index_migrates_to_log_and_back() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let data = tmp.child(".xdg/data/nrip");
+
+    set_backend(&tmp, "json");
+    bury(&tmp, "old.txt");
+    data.child("index.json").assert(predicate::path::exists());
+
+    // Lecture seule: l'ancien index est lu tel quel
+    set_backend(&tmp, "log");
+    Command::cargo_bin("nrip")?
+        .arg("-l")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old.txt"));
+    data.child("index.log").assert(predicate::path::missing());
+
+    // Première écriture: import unique
+    let f = tmp.child("new.txt");
+    f.write_str("n")?;
+    Command::cargo_bin("nrip")?
+        .arg(f.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("migrated index.json -> index.log (1 entries)"));
+    data.child("index.json").assert(predicate::path::missing());
+    data.child("index.json.migrated").assert(predicate::path::exists());
+    let log = std::fs::read_to_string(data.child("index.log").path())?;
+    // En-tête de version, puis une ligne par entrée
+    assert_eq!(log.lines().next(), Some(r#"{"version":1}"#));
+    assert_eq!(log.lines().count(), 3);
+    assert!(log.lines().skip(1).all(|l| l.starts_with(r#"{"op":"put""#)), "{log}");
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "old.txt", "-y"])
+        .assert()
+        .success();
+    tmp.child("old.txt").assert("old.txt");
+    let log = std::fs::read_to_string(data.child("index.log").path())?;
+    assert!(log.lines().last().unwrap().starts_with(r#"{"op":"del""#), "{log}");
+
+    Command::cargo_bin("nrip")?
+        .arg("-l")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("new.txt").and(predicate::str::contains("old.txt").not()));
+
+    // Retour à JSON: même chemin en sens inverse
+    set_backend(&tmp, "json");
+    bury(&tmp, "third.txt");
+    data.child("index.log.migrated").assert(predicate::path::exists());
+    let idx = std::fs::read_to_string(data.child("index.json").path())?;
+    assert!(idx.contains("new.txt") && idx.contains("third.txt") && !idx.contains("old.txt"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn torn_log_tail_is_ignored_then_overwritten() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    set_backend(&tmp, "log");
+    let log = tmp.child(".xdg/data/nrip/index.log");
+
+    bury(&tmp, "a.txt");
+    // Crash au milieu d'un append
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(log.path())?
+        .write_all(br#"{"op":"put","entry":{"original_pa"#)?;
+
+    Command::cargo_bin("nrip")?
+        .arg("-l")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("a.txt"));
+    bury(&tmp, "b.txt");
+
+    let text = std::fs::read_to_string(log.path())?;
+    assert_eq!(text.lines().count(), 3, "{text}");
+    assert!(text.ends_with('\n'));
+    assert!(text.contains("b.txt") && !text.contains("original_pa\""));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn log_is_compacted_when_mostly_dead() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    set_backend(&tmp, "log");
+    let log = tmp.child(".xdg/data/nrip/index.log");
+
+    let files: Vec<_> = (0..600)
+        .map(|i| {
+            let f = tmp.child(format!("src/f{i}"));
+            f.write_str("x").unwrap();
+            f.path().to_path_buf()
+        })
+        .collect();
+    Command::cargo_bin("nrip")?
+        .args(["-j", "4"])
+        .args(&files)
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(log.path())?.lines().count(), 601);
+
+    // 600 suppressions: 1200 lignes pour 0 entrée vivante → instantané
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    Command::cargo_bin("nrip")?
+        .args(["--prune", "--older-than", "0s", "-y"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(log.path())?, "{\"version\":1}\n");
+
+    bury(&tmp, "after.txt");
+    assert_eq!(std::fs::read_to_string(log.path())?.lines().count(), 2);
+    Ok(())
+}
@@ -0,0 +1,95 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use assert_fs::prelude::*;
+use serial_test::serial;
+use std::io::Write;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+mod util;
+
+fn isolate(tmp: &assert_fs::TempDir) {
+    // Graveyard principal obligatoire: le bury passe par le repli EXDEV
+    util::isolate_with_config(tmp, "per_mount_graveyards = false\n");
+}
+
+/// Autre FS que le dossier temporaire, ou None
+fn other_fs(tmp: &Path) -> Option<tempfile::TempDir> {
+    let shm = Path::new("/dev/shm");
+    let dev = |p: &Path| std::fs::metadata(p).map(|m| m.dev()).ok();
+    (shm.is_dir() && dev(shm) != dev(tmp))
+        .then(|| tempfile::tempdir_in(shm).ok())
+        .flatten()
+}
+
+fn write_big(p: &Path, mib: usize) {
+    let mut f = std::fs::File::create(p).unwrap();
+    let chunk = vec![0x5Au8; 1 << 20];
+    for _ in 0..mib {
+        f.write_all(&chunk).unwrap();
+    }
+}
+
+fn has_copying(dir: &Path) -> bool {
+    std::fs::read_dir(dir).is_ok_and(|rd| {
+        rd.filter_map(|e| e.ok())
+            .any(|e| e.file_name().to_string_lossy().ends_with(".copying"))
+    })
+}
+
+#[test]
+#[serial]
+fn sigint_during_cross_device_bury_cleans_up() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let Some(other) = other_fs(tmp.path()) else {
+        eprintln!("skip: no second filesystem available");
+        return Ok(());
+    };
+    isolate(&tmp);
+    let gy = tmp.child(".xdg/data/nrip/graveyard");
+
+    let done = tmp.child("small.txt");
+    done.write_str("s")?;
+    let big = other.path().join("big.bin");
+    write_big(&big, 1024);
+
+    let child = Command::new(cargo_bin("nrip"))
+        .arg(done.path())
+        .arg(&big)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let start = Instant::now();
+    while !has_copying(gy.path()) {
+        assert!(
+            start.elapsed() < Duration::from_secs(20),
+            "copy never started"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, libc::SIGINT);
+    }
+    let out = child.wait_with_output()?;
+    let stderr = String::from_utf8_lossy(&out.stderr);
+
+    assert_eq!(out.status.code(), Some(130), "stderr: {stderr}");
+    assert!(stderr.contains("Interrupted."));
+    // Pas de progression hors TTY
+    assert!(!stderr.contains('\r'));
+    // Source intacte, copie partielle supprimée
+    assert_eq!(std::fs::metadata(&big)?.len(), 1024 << 20);
+    assert!(!has_copying(gy.path()));
+
+    let journal = std::fs::read_to_string(gy.child(".journal").path())?;
+    assert!(journal.contains(&format!("ABORTED\t{}", big.display())));
+    // Ce qui était déjà enterré reste indexé
+    let index = std::fs::read_to_string(tmp.child(".xdg/data/nrip/index.json").path())?;
+    assert!(index.contains("small.txt"));
+    assert!(!index.contains("big.bin"));
+    done.assert(predicates::path::missing());
+    Ok(())
+}
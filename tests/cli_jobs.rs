@@ -0,0 +1,122 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+fn make_files(tmp: &assert_fs::TempDir, n: usize) -> Vec<std::path::PathBuf> {
+    (0..n)
+        .map(|i| {
+            let f = tmp.child(format!("src/f{i:03}.txt"));
+            f.write_str(&i.to_string()).unwrap();
+            f.path().to_path_buf()
+        })
+        .collect()
+}
+
+#[test]
+#[serial]
+fn parallel_bury_indexes_everything_in_one_commit() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "color = \"never\"\n");
+    let files = make_files(&tmp, 120);
+
+    Command::cargo_bin("nrip")?
+        .args(["-j", "8"])
+        .args(&files)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("f000.txt buried").and(predicate::str::contains("f119.txt buried")));
+    assert!(files.iter().all(|f| !f.exists()));
+
+    let v: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(tmp.child(".xdg/data/nrip/index.json").path())?)?;
+    let items = v["items"].as_array().unwrap();
+    assert_eq!(items.len(), 120);
+    for e in items {
+        assert!(std::path::Path::new(e["trashed_path"].as_str().unwrap()).exists());
+    }
+
+    let journal = std::fs::read_to_string(tmp.child(".xdg/data/nrip/graveyard/.journal").path())?;
+    let count = |tag: &str| journal.lines().filter(|l| l.starts_with(tag)).count();
+    assert_eq!((count("PENDING\t"), count("DONE\t"), count("COMMIT")), (120, 120, 1));
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "f042.txt", "-y"])
+        .assert()
+        .success();
+    tmp.child("src/f042.txt").assert("42");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn bury_reports_each_failure_and_keeps_the_rest() -> Result<(), Box<dyn std::error::Error>> {
+    // Même issue en séquentiel et en parallèle
+    for jobs in ["1", "3"] {
+        let tmp = assert_fs::TempDir::new()?;
+        util::isolate_with_config(&tmp, "color = \"never\"\n");
+        let mut files = make_files(&tmp, 4);
+        files.insert(2, tmp.child("src/missing.txt").path().to_path_buf());
+
+        Command::cargo_bin("nrip")?
+            .args(["--jobs", jobs])
+            .args(&files)
+            .assert()
+            .failure()
+            .stderr(
+                predicate::str::contains("cannot bury")
+                    .and(predicate::str::contains("missing.txt"))
+                    .and(predicate::str::contains("1 of 5 path(s) could not be buried")),
+            );
+
+        let index = std::fs::read_to_string(tmp.child(".xdg/data/nrip/index.json").path())?;
+        for i in 0..4 {
+            assert!(index.contains(&format!("f{i:03}.txt")), "--jobs {jobs}");
+            tmp.child(format!("src/f{i:03}.txt"))
+                .assert(predicate::path::missing());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn parallel_bury_into_freedesktop_trash() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "storage = \"freedesktop\"\ncolor = \"never\"\n");
+    let files = make_files(&tmp, 30);
+
+    Command::cargo_bin("nrip")?
+        .args(["-j", "0"])
+        .args(&files)
+        .assert()
+        .success();
+
+    let trash = tmp.child(".xdg/data/Trash");
+    let names = |sub: &str| -> Vec<String> {
+        let mut v: Vec<String> = std::fs::read_dir(trash.child(sub).path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        v.sort();
+        v
+    };
+    let files_in = names("files");
+    let infos: Vec<String> = names("info")
+        .into_iter()
+        .map(|n| n.trim_end_matches(".trashinfo").to_string())
+        .collect();
+    assert_eq!(files_in.len(), 30);
+    assert_eq!(files_in, infos);
+
+    Command::cargo_bin("nrip")?
+        .arg("-l")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("f029.txt"));
+    Ok(())
+}
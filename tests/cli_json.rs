@@ -0,0 +1,134 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use serde_json::Value;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+/// Lance nrip et décode stdout comme un seul document JSON
+fn json_doc(args: &[&str], ok: bool) -> Value {
+    let out = Command::cargo_bin("nrip").unwrap().args(args).output().unwrap();
+    assert_eq!(out.status.success(), ok, "{out:?}");
+    serde_json::from_slice(&out.stdout).unwrap_or_else(|e| panic!("{e}: {out:?}"))
+}
+
+#[test]
+#[serial]
+fn bury_and_list_as_json() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let f = tmp.child("notes.txt");
+    f.write_str("hello")?;
+    tmp.child("dir/a").write_str("a")?;
+
+    let doc = json_doc(
+        &["--json", f.path().to_str().unwrap(), tmp.child("dir").path().to_str().unwrap()],
+        true,
+    );
+    assert_eq!(doc["version"], 1);
+    assert_eq!(doc["command"], "bury");
+    assert_eq!(doc["ok"], true);
+    assert!(doc["error"].is_null());
+    let results = doc["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r["action"] == "buried"));
+    let e = &results[0]["entry"];
+    assert_eq!(e["original_path"], f.path().to_str().unwrap());
+    assert_eq!(e["kind"], "file");
+    assert_eq!(e["size"], 5);
+    assert_eq!(e["id"].as_str().unwrap().len(), 7);
+    assert!(e["deleted_at"].is_i64());
+    assert!(e["deleted_at_rfc3339"].as_str().unwrap().ends_with("+00:00"));
+    assert!(std::path::Path::new(e["trashed_path"].as_str().unwrap()).exists());
+    assert_eq!(results[1]["entry"]["kind"], "dir");
+
+    let doc = json_doc(&["--json", "-l"], true);
+    assert_eq!(doc["command"], "list");
+    let items = doc["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0], results[0]["entry"]);
+
+    // NDJSON: une entrée par ligne, rien d'autre sur stdout
+    let out = Command::cargo_bin("nrip")?.args(["--ndjson", "-l"]).output()?;
+    assert!(out.status.success());
+    let lines: Vec<Value> = String::from_utf8(out.stdout)?
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["kind"], "dir");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn failures_are_reported_per_item_and_in_the_document() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    tmp.child("a").write_str("a")?;
+    tmp.child("b").write_str("b")?;
+    let p = |n: &str| tmp.child(n).path().to_str().unwrap().to_owned();
+
+    let out = Command::cargo_bin("nrip")?
+        .args(["--ndjson", "-j", "2", &p("a"), &p("missing"), &p("b")])
+        .output()?;
+    assert!(!out.status.success());
+    // Les messages humains restent sur stderr
+    assert!(String::from_utf8(out.stderr)?.contains("a buried"));
+    let lines: Vec<Value> = String::from_utf8(out.stdout)?
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    let failed: Vec<_> = lines.iter().filter(|l| l["action"] == "failed").collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0]["path"], p("missing"));
+    assert_eq!(lines.iter().filter(|l| l["action"] == "buried").count(), 2);
+    assert!(
+        lines.last().unwrap()["error"]
+            .as_str()
+            .unwrap()
+            .contains("1 of 3 path(s) could not be buried")
+    );
+
+    let doc = json_doc(&["--json", "-r", "nothing-like-this"], true);
+    assert_eq!(doc["command"], "resurrect");
+    assert_eq!(doc["results"], serde_json::json!([]));
+
+    // Erreur fatale: document quand même, `ok` à false
+    let doc = json_doc(&["--json", "--prune"], false);
+    assert_eq!(doc["ok"], false);
+    assert!(doc["error"].as_str().unwrap().contains("no retention"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn resurrect_and_cremate_report_what_moved() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let keep = tmp.child("keep.txt");
+    keep.write_str("k")?;
+    let burn = tmp.child("burn.txt");
+    burn.write_str("b")?;
+    Command::cargo_bin("nrip")?
+        .arg(keep.path())
+        .arg(burn.path())
+        .assert()
+        .success();
+
+    let doc = json_doc(&["--json", "-r", "keep", "-y"], true);
+    let results = doc["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["action"], "restored");
+    assert_eq!(results[0]["entry"]["original_path"], keep.path().to_str().unwrap());
+    keep.assert("k");
+
+    let doc = json_doc(&["--json", "-c", "burn", "-y"], true);
+    assert_eq!(doc["command"], "cremate");
+    let results = doc["results"].as_array().unwrap();
+    assert_eq!(results[0]["action"], "cremated");
+    let trashed = results[0]["entry"]["trashed_path"].as_str().unwrap();
+    assert!(!std::path::Path::new(trashed).exists());
+    Ok(())
+}
@@ -156,4 +156,186 @@ fn list_output_is_properly_formatted() -> Result<(), Box<dyn std::error::Error>>
 
     tmp.close()?;
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+#[serial]
+fn list_format_and_time_format_flags() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::set_var("HOME", tmp.path());
+    util::set_var("XDG_DATA_HOME", tmp.child(".xdg/data").path());
+    util::set_var("XDG_CONFIG_HOME", tmp.child(".xdg/config").path());
+
+    let file = tmp.child("note.txt");
+    file.write_str("12345")?;
+    Command::cargo_bin("nrip")?.arg(file.path()).assert().success();
+
+    let output = Command::cargo_bin("nrip")?
+        .args(["--list", "--color", "never", "--time-format", "%Y"])
+        .args(["--format", "[{basename:^10}] {{{kind}}} {size:>6}|{deleted_at}|{graveyard}"])
+        .output()?;
+    assert!(output.status.success());
+    let year = chrono::Local::now().format("%Y").to_string();
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        format!("[ note.txt ] {{F}}    5 B|{year}|-\n")
+    );
+
+    let output = Command::cargo_bin("nrip")?
+        .args(["--list", "--color", "never", "--format", "{trashed_path}"])
+        .output()?;
+    let trashed = String::from_utf8(output.stdout)?;
+    assert!(trashed.trim_end().ends_with("__note.txt"), "{trashed}");
+    assert!(std::path::Path::new(trashed.trim_end()).exists());
+
+    // Champ inconnu: refusé avant toute lecture
+    let output = Command::cargo_bin("nrip")?
+        .args(["--list", "--format", "{id} {owner}"])
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("`list.format`: unknown field '{owner}'"));
+    Ok(())
+}
+
+/// Index fabriqué: âges, types et tailles connus sans attendre
+fn seed_index(tmp: &assert_fs::TempDir) {
+    let now = chrono::Utc::now().timestamp();
+    let entry = |orig: &str, age: i64, kind: &str, size: u64| {
+        let name = orig.rsplit('/').next().unwrap();
+        serde_json::json!({
+            "original_path": orig,
+            "trashed_path": format!("/gy/20250101T000000__Ab{size:05}__{name}"),
+            "deleted_at": now - age,
+            "kind": kind,
+            "size": size,
+            "disk_usage": size,
+        })
+    };
+    let doc = serde_json::json!({
+        "version": 1,
+        "items": [
+            entry("/home/u/src/main.rs", 3 * 86400, "File", 300),
+            entry("/home/u/src/target", 2 * 3600, "Dir", 90000),
+            entry("/home/u/notes/Todo.md", 60, "File", 10),
+            entry("/tmp/link", 10 * 86400, "Symlink", 0),
+        ],
+    });
+    tmp.child(".xdg/data/nrip/index.json")
+        .write_str(&doc.to_string())
+        .unwrap();
+}
+
+fn names(args: &[&str]) -> Vec<String> {
+    let out = Command::cargo_bin("nrip")
+        .unwrap()
+        .args(["--list", "--format", "{basename}"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    String::from_utf8(out.stdout).unwrap().lines().map(str::to_owned).collect()
+}
+
+#[test]
+#[serial]
+fn list_sorts_and_filters() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::set_var("HOME", tmp.path());
+    util::set_var("XDG_DATA_HOME", tmp.child(".xdg/data").path());
+    util::set_var("XDG_CONFIG_HOME", tmp.child(".xdg/config").path());
+    seed_index(&tmp);
+
+    assert_eq!(names(&[]), ["main.rs", "target", "Todo.md", "link"]);
+    assert_eq!(names(&["--sort", "date"]), ["link", "main.rs", "target", "Todo.md"]);
+    assert_eq!(names(&["--sort", "name"]), ["link", "main.rs", "target", "Todo.md"]);
+    assert_eq!(names(&["--sort", "size", "--reverse"]), ["target", "main.rs", "Todo.md", "link"]);
+    assert_eq!(names(&["--sort", "kind"]), ["main.rs", "Todo.md", "target", "link"]);
+    assert_eq!(names(&["--sort", "path", "--limit", "2"]), ["Todo.md", "main.rs"]);
+
+    assert_eq!(names(&["--since", "1d"]), ["target", "Todo.md"]);
+    assert_eq!(names(&["--before", "1d"]), ["main.rs", "link"]);
+    assert_eq!(names(&["--since", "7d", "--before", "1h"]), ["main.rs", "target"]);
+    assert_eq!(names(&["--kind", "file,symlink"]), ["main.rs", "Todo.md", "link"]);
+    assert_eq!(names(&["--under", "/home/u/src"]), ["main.rs", "target"]);
+    // `--under` compare des composants entiers
+    assert!(names(&["--under", "/home/u/sr"]).is_empty());
+
+    assert_eq!(names(&["--match", "*.rs"]), ["main.rs"]);
+    assert_eq!(names(&["--match", "/home/*/notes/*"]), ["Todo.md"]);
+    // Sans '/', `*` ne traverse pas les dossiers: le motif porte sur le nom seul
+    assert!(names(&["--match", "home*"]).is_empty());
+    assert_eq!(names(&["--match", "re:^/home/.*/t"]), ["target"]);
+    assert_eq!(names(&["--match", "re:(?i)todo", "--kind", "file"]), ["Todo.md"]);
+
+    // Les filtres s'appliquent aussi à la sortie machine
+    let out = Command::cargo_bin("nrip")?
+        .args(["--list", "--json", "--kind", "dir"])
+        .output()?;
+    let doc: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(doc["items"].as_array().unwrap().len(), 1);
+
+    for (args, err) in [
+        (["--sort", "age"], "expected date|name|size|path|kind"),
+        (["--kind", "pipe"], "expected file|dir|symlink|other"),
+        (["--match", "re:("], "invalid regex"),
+        (["--since", "soon"], "invalid value 'soon'"),
+    ] {
+        let out = Command::cargo_bin("nrip")?.arg("--list").args(args).output()?;
+        assert!(!out.status.success());
+        assert!(String::from_utf8(out.stderr)?.contains(err), "{args:?}");
+    }
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn list_tree_groups_by_original_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::set_var("HOME", tmp.path());
+    util::set_var("XDG_DATA_HOME", tmp.child(".xdg/data").path());
+    util::set_var("XDG_CONFIG_HOME", tmp.child(".xdg/config").path());
+    seed_index(&tmp);
+
+    let tree = |args: &[&str]| -> Vec<String> {
+        let out = Command::cargo_bin("nrip")
+            .unwrap()
+            .args(["--list", "--tree", "--color", "never"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{out:?}");
+        // Âges variables: on ne garde que la structure et les noms
+        String::from_utf8(out.stdout)
+            .unwrap()
+            .lines()
+            .map(|l| l.rsplit_once("  (").map_or(l, |(head, _)| head).to_owned())
+            .collect()
+    };
+
+    assert_eq!(
+        tree(&[]),
+        [
+            "/",
+            "├── home/u/",
+            "│   ├── notes/",
+            "│   │   └── 📄 Todo.md  10 B  Ab00010",
+            "│   └── src/",
+            "│       ├── 📄 main.rs  300 B  Ab00300",
+            "│       └── 📁 target  87.9 KiB  Ab90000",
+            "└── tmp/",
+            "    └── 🔗 link  0 B  Ab00000",
+        ]
+    );
+
+    // Préfixe commun fusionné, bilan par dossier; les filtres s'appliquent avant
+    let out = Command::cargo_bin("nrip")?
+        .args(["--list", "--tree", "--color", "never", "--under", "/home/u/src"])
+        .output()?;
+    let text = String::from_utf8(out.stdout)?;
+    assert!(text.starts_with("/home/u/src/  (2 item(s), 88.2 KiB)\n"), "{text}");
+
+    let out = Command::cargo_bin("nrip")?
+        .args(["--list", "--tree", "--sort", "size"])
+        .output()?;
+    assert!(!out.status.success());
+    Ok(())
+}
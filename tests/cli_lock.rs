@@ -0,0 +1,123 @@
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use rustix::fs::{FlockOperation, flock};
+use serial_test::serial;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+mod util;
+
+fn isolate(tmp: &assert_fs::TempDir) {
+    util::isolate(tmp);
+    util::remove_var("NRIP_LOCK_TIMEOUT");
+}
+
+/// Tient le verrou exclusif comme le ferait un nrip bloqué
+fn hold_lock(tmp: &assert_fs::TempDir) -> std::fs::File {
+    let dir = tmp.child(".xdg/data/nrip");
+    dir.create_dir_all().unwrap();
+    let mut f = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(dir.child(".index.lock").path())
+        .unwrap();
+    flock(&f, FlockOperation::NonBlockingLockExclusive).unwrap();
+    let since = chrono::Utc::now().timestamp() - 90;
+    writeln!(f, "4242\t{since}\tnrip -r stuck").unwrap();
+    f
+}
+
+#[test]
+#[serial]
+fn no_wait_fails_fast_and_names_the_holder() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate(&tmp);
+    let f = tmp.child("f.txt");
+    f.write_str("f")?;
+    let _held = hold_lock(&tmp);
+
+    let start = Instant::now();
+    Command::cargo_bin("nrip")?
+        .arg("--no-wait")
+        .arg(f.path())
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("index is locked by PID 4242")
+                .and(predicate::str::contains("`nrip -r stuck`, for 1m 30s"))
+                .and(predicate::str::contains("not waiting")),
+        );
+    assert!(start.elapsed() < Duration::from_secs(2));
+    f.assert("f");
+
+    // Les lectures attendent aussi un écrivain
+    Command::cargo_bin("nrip")?
+        .args(["--no-wait", "-l"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("PID 4242"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn lock_timeout_is_configurable() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate(&tmp);
+    tmp.child(".xdg/config/nrip/config.toml")
+        .write_str("[lock]\ntimeout = \"1500ms\"\n")?;
+    let f = tmp.child("f.txt");
+    f.write_str("f")?;
+    let held = hold_lock(&tmp);
+
+    let start = Instant::now();
+    Command::cargo_bin("nrip")?
+        .arg(f.path())
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("waiting for the index lock held by PID 4242")
+                .and(predicate::str::contains("gave up after 1s 500ms")),
+        );
+    assert!(start.elapsed() >= Duration::from_millis(1500));
+
+    // Verrou relâché pendant l'attente: l'opération passe
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(300));
+        drop(held);
+    });
+    Command::cargo_bin("nrip")?
+        .arg(f.path())
+        .env("NRIP_LOCK_TIMEOUT", "5s")
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success();
+    release.join().unwrap();
+    f.assert(predicate::path::missing());
+
+    // Plus personne ne tient le verrou: fichier vidé
+    tmp.child(".xdg/data/nrip/.index.lock").assert("");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn invalid_lock_timeout_is_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    isolate(&tmp);
+    Command::cargo_bin("nrip")?
+        .arg("-l")
+        .env("NRIP_LOCK_TIMEOUT", "soon")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`lock.timeout`: invalid duration 'soon'"));
+    Ok(())
+}
@@ -0,0 +1,151 @@
+#![cfg(unix)]
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+mod util;
+
+/// Dossier temporaire sur un autre FS que `tmp` (tmpfs /dev/shm), sinon None → skip
+fn other_fs_tempdir(tmp: &Path) -> Option<tempfile::TempDir> {
+    let shm = Path::new("/dev/shm");
+    let dev_tmp = std::fs::metadata(tmp).ok()?.dev();
+    let dev_shm = std::fs::metadata(shm).ok()?.dev();
+    if dev_tmp == dev_shm {
+        return None;
+    }
+    tempfile::tempdir_in(shm).ok()
+}
+
+fn mount_graveyard() -> PathBuf {
+    Path::new("/dev/shm").join(format!(
+        ".nrip-graveyard-{}",
+        std::fs::metadata("/proc/self").map(|m| m.uid()).unwrap_or(0)
+    ))
+}
+
+#[test]
+#[serial]
+fn bury_on_other_mount_uses_mount_graveyard() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let Some(other) = other_fs_tempdir(tmp.path()) else {
+        eprintln!("skip: no second filesystem available");
+        return Ok(());
+    };
+
+    util::set_var("HOME", tmp.path());
+    util::set_var("XDG_DATA_HOME", tmp.child(".xdg/data").path());
+    util::set_var("XDG_CONFIG_HOME", tmp.child(".xdg/config").path());
+
+    let src = other.path().join("far-away.txt");
+    std::fs::write(&src, "far")?;
+
+    Command::cargo_bin("nrip")?.arg(&src).assert().success();
+    assert!(!src.exists());
+
+    // Rangé au sommet du montage, pas dans le graveyard principal
+    let gy = mount_graveyard();
+    let buried: Vec<PathBuf> = std::fs::read_dir(&gy)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.to_string_lossy().ends_with("__far-away.txt"))
+        .collect();
+    assert_eq!(buried.len(), 1, "expected one burial in {}", gy.display());
+
+    // L'index sait quel graveyard détient l'entrée
+    let idx_str = std::fs::read_to_string(tmp.child(".xdg/data/nrip/index.json").path())?;
+    assert!(idx_str.contains(r#""graveyard""#), "index: {idx_str}");
+
+    Command::cargo_bin("nrip")?
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("far-away.txt"));
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "far-away", "-y"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&src)?, "far");
+    assert!(!buried[0].exists());
+
+    let _ = std::fs::remove_dir(&gy);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn per_mount_graveyards_can_be_disabled() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let Some(other) = other_fs_tempdir(tmp.path()) else {
+        eprintln!("skip: no second filesystem available");
+        return Ok(());
+    };
+
+    util::set_var("HOME", tmp.path());
+    util::set_var("XDG_DATA_HOME", tmp.child(".xdg/data").path());
+    util::set_var("XDG_CONFIG_HOME", tmp.child(".xdg/config").path());
+    tmp.child(".xdg/config/nrip/config.toml")
+        .write_str("per_mount_graveyards = false\n")?;
+
+    let src = other.path().join("copied.txt");
+    std::fs::write(&src, "copied")?;
+
+    // Repli EXDEV: copie vers le graveyard principal
+    Command::cargo_bin("nrip")?.arg(&src).assert().success();
+    assert!(!src.exists());
+    let home_gy = tmp.child(".xdg/data/nrip/graveyard");
+    let n = std::fs::read_dir(home_gy.path())?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().ends_with("__copied.txt"))
+        .count();
+    assert_eq!(n, 1);
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "copied", "-y"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&src)?, "copied");
+    Ok(())
+}
+
+#[test]
+fn mount_graveyard_itself_is_protected() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let fake = tmp.child(".nrip-graveyard-1000");
+    fake.create_dir_all()?;
+    fake.child("x").write_str("x")?;
+
+    Command::cargo_bin("nrip")?
+        .env("XDG_DATA_HOME", tmp.child(".xdg/data").path())
+        .env("XDG_CONFIG_HOME", tmp.child(".xdg/config").path())
+        .arg(fake.child("x").path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("inside the graveyard"));
+    Ok(())
+}
+
+#[test]
+fn trash_like_dirs_below_the_mount_top_can_be_buried() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let uid = std::fs::metadata("/proc/self").map(|m| m.uid()).unwrap_or(0);
+    let old = tmp.child("notes/.Trash-old/x");
+    let mine = tmp.child(format!("notes/.Trash-{uid}/y"));
+    old.write_str("x")?;
+    mine.write_str("y")?;
+
+    Command::cargo_bin("nrip")?
+        .env("XDG_DATA_HOME", tmp.child(".xdg/data").path())
+        .env("XDG_CONFIG_HOME", tmp.child(".xdg/config").path())
+        .args([old.path(), mine.path()])
+        .assert()
+        .success();
+    old.assert(predicate::path::missing());
+    mine.assert(predicate::path::missing());
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+/// Vieillit artificiellement les entrées dont le basename contient `name`
+fn backdate(tmp: &assert_fs::TempDir, name: &str, days: i64) {
+    let index = tmp.child(".xdg/data/nrip/index.json");
+    let mut v: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(index.path()).unwrap()).unwrap();
+    for e in v["items"].as_array_mut().unwrap() {
+        if e["original_path"].as_str().unwrap().ends_with(name) {
+            let ts = e["deleted_at"].as_i64().unwrap();
+            e["deleted_at"] = serde_json::json!(ts - days * 86_400);
+        }
+    }
+    std::fs::write(index.path(), serde_json::to_vec_pretty(&v).unwrap()).unwrap();
+}
+
+#[test]
+#[serial]
+fn prune_older_than_removes_only_expired_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    let old = tmp.child("ancient.txt");
+    let fresh = tmp.child("fresh.txt");
+    old.write_str("old")?;
+    fresh.write_str("new")?;
+    Command::cargo_bin("nrip")?.arg(old.path()).assert().success();
+    Command::cargo_bin("nrip")?.arg(fresh.path()).assert().success();
+    backdate(&tmp, "ancient.txt", 10);
+
+    // dry-run: rapporte sans toucher
+    Command::cargo_bin("nrip")?
+        .args(["--prune", "--older-than", "1d", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("ancient.txt")
+                .and(predicate::str::contains("fresh.txt").not())
+                .and(predicate::str::contains("nothing deleted")),
+        );
+    let idx = tmp.child(".xdg/data/nrip/index.json");
+    assert!(std::fs::read_to_string(idx.path())?.contains("ancient.txt"));
+
+    Command::cargo_bin("nrip")?
+        .args(["--prune", "--older-than", "1d", "-y"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Pruned 1 item(s)."));
+    let idx_str = std::fs::read_to_string(idx.path())?;
+    assert!(!idx_str.contains("ancient.txt"));
+    assert!(idx_str.contains("fresh.txt"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn prune_without_retention_fails() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    Command::cargo_bin("nrip")?
+        .args(["--prune", "-y"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("retention"));
+
+    Command::cargo_bin("nrip")?
+        .args(["--prune", "--older-than", "soon"])
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn auto_prune_runs_after_bury_with_configured_retention() -> Result<(), Box<dyn std::error::Error>>
+{
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    let old = tmp.child("stale.txt");
+    old.write_str("old")?;
+    Command::cargo_bin("nrip")?.arg(old.path()).assert().success();
+    backdate(&tmp, "stale.txt", 40);
+
+    tmp.child(".xdg/config/nrip/config.toml")
+        .write_str("[prune]\nretention = \"30d\"\nauto = true\n")?;
+
+    let next = tmp.child("next.txt");
+    next.write_str("x")?;
+    Command::cargo_bin("nrip")?
+        .arg(next.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pruned 1 item(s)"));
+
+    let idx_str = std::fs::read_to_string(tmp.child(".xdg/data/nrip/index.json").path())?;
+    assert!(!idx_str.contains("stale.txt"));
+    assert!(idx_str.contains("next.txt"));
+
+    // --prune sans --older-than utilise la rétention configurée
+    Command::cargo_bin("nrip")?
+        .args(["--prune", "-y"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing buried for more than 30days"));
+    Ok(())
+}
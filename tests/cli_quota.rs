@@ -0,0 +1,171 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+#[test]
+#[serial]
+fn quota_evicts_oldest_entries_first() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "[quota]\nmax_size = \"3000B\"\n");
+
+    let a = tmp.child("first.bin");
+    let b = tmp.child("second.bin");
+    a.write_binary(&[0u8; 2000])?;
+    b.write_binary(&[1u8; 2000])?;
+
+    Command::cargo_bin("nrip")?.arg(a.path()).assert().success();
+    Command::cargo_bin("nrip")?
+        .arg(b.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("first.bin cremated to make room"));
+
+    let idx = util::index_str(&tmp);
+    assert!(!idx.contains("first.bin"));
+    assert!(idx.contains("second.bin"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn quota_counts_directory_contents_recursively() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "[quota]\nmax_size = \"8KiB\"\n");
+
+    // Le dossier lui-même est petit, son contenu non
+    let dir = tmp.child("big-dir");
+    dir.child("nested/deep.bin").write_binary(&[0u8; 10_000])?;
+
+    Command::cargo_bin("nrip")?
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("exceeds quota.max_size"));
+    dir.assert(predicate::path::exists());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn quota_refuse_policy_keeps_graveyard_and_source() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(
+        &tmp,
+        "[quota]\nmax_size = \"3000\"\npolicy = \"refuse\"\n",
+    );
+
+    let a = tmp.child("kept.bin");
+    let b = tmp.child("refused.bin");
+    a.write_binary(&[0u8; 2000])?;
+    b.write_binary(&[1u8; 2000])?;
+
+    Command::cargo_bin("nrip")?.arg(a.path()).assert().success();
+    Command::cargo_bin("nrip")?
+        .arg(b.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("quota exceeded"));
+
+    b.assert(predicate::path::exists());
+    assert!(util::index_str(&tmp).contains("kept.bin"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn quota_max_items_evicts_by_count() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "[quota]\nmax_items = 2\n");
+
+    for name in ["one.txt", "two.txt", "three.txt"] {
+        let f = tmp.child(name);
+        f.write_str(name)?;
+        Command::cargo_bin("nrip")?.arg(f.path()).assert().success();
+    }
+
+    let idx = util::index_str(&tmp);
+    assert!(!idx.contains("one.txt"));
+    assert!(idx.contains("two.txt") && idx.contains("three.txt"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn refused_burial_evicts_nothing() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "[quota]\nmax_items = 1\n");
+
+    let a = tmp.child("a.txt");
+    a.write_str("a")?;
+    Command::cargo_bin("nrip")?.arg(a.path()).assert().success();
+    Command::cargo_bin("nrip")?
+        .arg("/")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("cremated to make room").not());
+
+    let idx = util::index_str(&tmp);
+    let trashed = idx
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("\"trashed_path\": \""))
+        .map(|l| l.trim_end_matches("\",").to_owned())
+        .unwrap();
+    assert!(std::path::Path::new(&trashed).exists());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn evictions_are_journaled() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "[quota]\nmax_items = 1\n");
+
+    for name in ["old.txt", "new.txt"] {
+        let f = tmp.child(name);
+        f.write_str(name)?;
+        Command::cargo_bin("nrip")?.arg(f.path()).assert().success();
+    }
+    let journal = std::fs::read_to_string(tmp.child(".xdg/data/nrip/graveyard/.journal").path())?;
+    let evict = journal.lines().find(|l| l.starts_with("EVICT\t")).unwrap();
+    assert!(evict.ends_with("__old.txt"), "{evict}");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn quota_ignores_mount_graveyards() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::MetadataExt;
+
+    let tmp = assert_fs::TempDir::new()?;
+    let shm = std::path::Path::new("/dev/shm");
+    if !shm.is_dir() || shm.metadata()?.dev() == tmp.path().metadata()?.dev() {
+        eprintln!("skipped: no second filesystem");
+        return Ok(());
+    }
+    util::isolate_with_config(&tmp, "[quota]\nmax_items = 1\n");
+    let elsewhere = tempfile::tempdir_in(shm)?;
+    let home = tmp.child("home.txt");
+    home.write_str("home")?;
+    let away = elsewhere.path().join("away.txt");
+    std::fs::write(&away, "away")?;
+
+    Command::cargo_bin("nrip")?.arg(home.path()).assert().success();
+    // Enterré dans le graveyard de /dev/shm: hors quota, rien n'est évincé
+    Command::cargo_bin("nrip")?
+        .arg(&away)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cremated to make room").not());
+
+    let idx = util::index_str(&tmp);
+    assert!(idx.contains("home.txt") && idx.contains("away.txt"));
+    Command::cargo_bin("nrip")?
+        .args(["-r", "away.txt", "-y"])
+        .assert()
+        .success();
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::io::Write;
+use std::process::Command;
+
+mod util;
+
+/// Simule les lignes qu'un `nrip` tué en plein travail aurait laissées
+fn journal(tmp: &assert_fs::TempDir, line: &str) {
+    let gy = tmp.child(".xdg/data/nrip/graveyard");
+    std::fs::create_dir_all(gy.path()).unwrap();
+    let mut f = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(gy.child(".journal").path())
+        .unwrap();
+    writeln!(f, "{line}").unwrap();
+}
+
+#[test]
+#[serial]
+fn moved_but_unindexed_item_is_adopted() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    // Un enterrement normal est clos: rien à rejouer
+    let kept = tmp.child("kept.txt");
+    kept.write_str("k")?;
+    Command::cargo_bin("nrip")?.arg(kept.path()).assert().success();
+    Command::cargo_bin("nrip")?
+        .arg("--recover")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to recover."));
+
+    // Tué entre le déplacement et l'écriture de l'index
+    let original = tmp.child("lost.txt");
+    let trashed = tmp.child(".xdg/data/nrip/graveyard/20260101T000000__abcdefgh__lost.txt");
+    trashed.write_str("precious")?;
+    journal(
+        &tmp,
+        &format!(
+            "PENDING\t{}\t{}",
+            original.path().display(),
+            trashed.path().display()
+        ),
+    );
+
+    Command::cargo_bin("nrip")?
+        .arg("--recover")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Recovered 1 interrupted operation(s)."));
+    assert!(util::index_str(&tmp).contains("lost.txt"));
+    // Date d'enterrement lue dans le nom du graveyard, pas l'heure de la reprise
+    assert!(util::index_str(&tmp).contains("\"deleted_at\": 1767225600"));
+
+    // La passe est idempotente
+    Command::cargo_bin("nrip")?
+        .arg("--recover")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to recover."));
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "lost", "-y"])
+        .assert()
+        .success();
+    original.assert("precious");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn interrupted_copy_is_rolled_back_on_next_command() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    // Tué pendant la copie inter-device: la source est intacte, la copie partielle traîne
+    let src = tmp.child("big-dir");
+    src.child("a.bin").write_str("data")?;
+    let dst = tmp.child(".xdg/data/nrip/graveyard/20260101T000000__abcdefgh__big-dir");
+    let partial = tmp.child(".xdg/data/nrip/graveyard/20260101T000000__abcdefgh__big-dir.copying");
+    partial.child("a.bin").write_str("da")?;
+    journal(
+        &tmp,
+        &format!(
+            "PENDING\t{}\t{}",
+            src.path().display(),
+            dst.path().display()
+        ),
+    );
+
+    // N'importe quelle commande qui modifie l'index rejoue d'abord le journal
+    let other = tmp.child("other.txt");
+    other.write_str("o")?;
+    Command::cargo_bin("nrip")?
+        .arg(other.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("rolled back"));
+
+    partial.assert(predicate::path::missing());
+    src.child("a.bin").assert("data");
+    let idx = util::index_str(&tmp);
+    assert!(!idx.contains("big-dir"));
+    assert!(idx.contains("other.txt"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn finished_restore_is_dropped_from_index() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    let file = tmp.child("back.txt");
+    file.write_str("b")?;
+    Command::cargo_bin("nrip")?.arg(file.path()).assert().success();
+
+    // Tué après le rename de restauration, avant l'écriture de l'index
+    let v: serde_json::Value = serde_json::from_str(&util::index_str(&tmp))?;
+    let trashed = v["items"][0]["trashed_path"].as_str().unwrap().to_string();
+    journal(
+        &tmp,
+        &format!("RESTORE_PENDING\t{trashed}\t{}", file.path().display()),
+    );
+    std::fs::rename(&trashed, file.path())?;
+
+    Command::cargo_bin("nrip")?
+        .arg("--recover")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Recovered 1"));
+    assert!(!util::index_str(&tmp).contains("back.txt"));
+    file.assert("b");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn interrupted_eviction_is_completed() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let f = tmp.child("evicted.txt");
+    f.write_str("e")?;
+    Command::cargo_bin("nrip")?.arg(f.path()).assert().success();
+    let idx = util::index_str(&tmp);
+    let trashed = idx
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("\"trashed_path\": \""))
+        .map(|l| l.trim_end_matches("\",").to_owned())
+        .unwrap();
+
+    // Tué pendant la suppression, avant l'écriture de l'index
+    journal(&tmp, &format!("EVICT\t{trashed}"));
+
+    Command::cargo_bin("nrip")?
+        .arg("--recover")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("interrupted eviction"));
+    assert!(!std::path::Path::new(&trashed).exists());
+    assert!(!util::index_str(&tmp).contains("evicted.txt"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn closed_journal_is_rotated_once_large() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let gy = tmp.child(".xdg/data/nrip/graveyard");
+    for i in 0..4000 {
+        journal(&tmp, &format!("RECOVERED\tROLLED_BACK\t/tmp/old/{i:0>64}"));
+    }
+    journal(&tmp, "COMMIT");
+
+    let f = tmp.child("fresh.txt");
+    f.write_str("f")?;
+    Command::cargo_bin("nrip")?.arg(f.path()).assert().success();
+    let rotated = std::fs::read_to_string(gy.child(".journal.1").path())?;
+    assert!(rotated.contains("fresh.txt") && rotated.ends_with("COMMIT\n"));
+    assert!(!gy.child(".journal").path().exists());
+
+    // Le journal repart de zéro; l'élément se restaure normalement
+    Command::cargo_bin("nrip")?
+        .args(["-r", "fresh", "-y"])
+        .assert()
+        .success();
+    f.assert("f");
+    let current = std::fs::read_to_string(gy.child(".journal").path())?;
+    assert!(current.lines().count() < 10);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn failed_move_is_journaled_and_settled() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    // Nom valide ici, trop long une fois préfixé dans le graveyard: le rename échoue
+    let long = tmp.child("x".repeat(240));
+    long.write_str("long")?;
+    Command::cargo_bin("nrip")?
+        .arg(long.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot bury"));
+    long.assert("long");
+
+    let journal = std::fs::read_to_string(tmp.child(".xdg/data/nrip/graveyard/.journal").path())?;
+    let lines: Vec<&str> = journal.lines().collect();
+    let failed = lines.iter().position(|l| l.starts_with("FAILED\t")).unwrap();
+    assert_eq!(lines.get(failed + 1), Some(&"COMMIT"), "{journal}");
+    assert!(!util::index_str(&tmp).contains(&"x".repeat(240)));
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serde_json::Value;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+/// Chemins d'origine encore enterrés
+fn listed() -> Vec<String> {
+    util::listed()
+        .iter()
+        .map(|e| e["original_path"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+#[test]
+#[serial]
+fn fail_keeps_what_was_restored_before_the_conflict() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    // Restaurés parents d'abord: a.txt (moins profond) passe avant sub/b.txt
+    let a = tmp.child("a.txt");
+    let b = tmp.child("sub/b.txt");
+    a.write_str("a")?;
+    b.write_str("old b")?;
+    util::bury(a.path());
+    util::bury(b.path());
+    b.write_str("new b")?;
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "", "-y"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Target already exists")
+                .and(predicate::str::contains("1 item(s) restored before the error")),
+        );
+    a.assert("a");
+    b.assert("new b");
+    // L'index a suivi: seul b.txt reste, rien à rattraper
+    assert_eq!(listed(), [b.path().to_str().unwrap()]);
+    Command::cargo_bin("nrip")?
+        .arg("--recover")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to recover."));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn skip_leaves_the_item_buried_and_goes_on() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let a = tmp.child("a.txt");
+    let b = tmp.child("sub/b.txt");
+    a.write_str("old a")?;
+    b.write_str("b")?;
+    util::bury(a.path());
+    util::bury(b.path());
+    a.write_str("new a")?;
+
+    let out = Command::cargo_bin("nrip")?
+        .args(["-r", "", "-y", "--on-conflict", "skip", "--ndjson"])
+        .output()?;
+    assert!(out.status.success(), "{out:?}");
+    let stderr = String::from_utf8(out.stderr)?;
+    assert!(stderr.contains("Skipped a.txt") && stderr.contains("Restored 1 item(s)."), "{stderr}");
+    let actions: Vec<Value> = String::from_utf8(out.stdout)?
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(actions[0]["action"], "skipped");
+    assert_eq!(actions[0]["path"], a.path().to_str().unwrap());
+    assert_eq!(actions[1]["action"], "restored");
+    a.assert("new a");
+    b.assert("b");
+    assert_eq!(listed(), [a.path().to_str().unwrap()]);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn rename_picks_the_next_free_name() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let f = tmp.child("notes.txt");
+    for version in ["v1", "v2"] {
+        f.write_str(version)?;
+        util::bury(f.path());
+    }
+    f.write_str("current")?;
+    let d = tmp.child("build");
+    d.child("out").write_str("o")?;
+    util::bury(d.path());
+    d.create_dir_all()?;
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "", "-y", "--all-generations", "--on-conflict", "rename"])
+        .assert()
+        .success();
+    f.assert("current");
+    let mut versions = vec![
+        std::fs::read_to_string(tmp.child("notes (restored 2).txt").path())?,
+        std::fs::read_to_string(tmp.child("notes (restored 3).txt").path())?,
+    ];
+    versions.sort();
+    assert_eq!(versions, ["v1", "v2"]);
+    tmp.child("build (restored 2)/out").assert("o");
+    assert!(listed().is_empty());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn overwrite_bury_swaps_the_occupant_into_the_graveyard() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let f = tmp.child("config.toml");
+    f.write_str("old")?;
+    util::bury(f.path());
+    f.write_str("new")?;
+
+    let out = Command::cargo_bin("nrip")?
+        .args(["-r", "config", "-y", "--on-conflict", "overwrite-bury", "--json"])
+        .output()?;
+    assert!(out.status.success(), "{out:?}");
+    f.assert("old");
+
+    let doc: Value = serde_json::from_slice(&out.stdout)?;
+    let results = doc["results"].as_array().unwrap();
+    assert_eq!(results[0]["action"], "buried");
+    assert_eq!(results[1]["action"], "restored");
+    // L'occupant est au graveyard, indexé, et se restaure à son tour
+    let occupant = results[0]["entry"]["trashed_path"].as_str().unwrap();
+    assert_eq!(std::fs::read_to_string(occupant)?, "new");
+    assert_eq!(listed(), [f.path().to_str().unwrap()]);
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "config", "-y", "--on-conflict", "maybe"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected skip|rename|overwrite-bury|fail"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn children_follow_a_renamed_parent() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let proj = tmp.child("proj");
+    proj.child("a.txt").write_str("a")?;
+    proj.child("b.txt").write_str("b")?;
+    util::bury(proj.child("a.txt").path());
+    util::bury(proj.path());
+    proj.child("unrelated.txt").write_str("u")?;
+
+    Command::cargo_bin("nrip")?
+        .args(["-r", "a.txt", "-y", "--on-conflict", "rename"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("restoring proj as")
+                .and(predicate::str::contains("proj (restored 2)")),
+        );
+    let renamed = tmp.child("proj (restored 2)");
+    renamed.child("a.txt").assert("a");
+    renamed.child("b.txt").assert("b");
+    proj.child("a.txt").assert(predicate::path::missing());
+    assert!(listed().is_empty());
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serde_json::Value;
+use serial_test::serial;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::process::Command;
+
+mod util;
+
+#[test]
+#[serial]
+fn extracts_one_file_and_keeps_the_rest_buried() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let project = tmp.child("project");
+    project.child("src/main.rs").write_str("fn main() {}")?;
+    project.child("src/lib.rs").write_str("// lib")?;
+    project.child("README.md").write_str("readme")?;
+    util::bury(project.path());
+    let size_before = util::listed()[0]["size"].as_u64().unwrap();
+
+    // Chemin relatif au dossier courant, comme l'original
+    let out = Command::cargo_bin("nrip")?
+        .current_dir(tmp.path())
+        .args(["-r", "project/README.md", "-y", "--ndjson"])
+        .output()?;
+    assert!(out.status.success(), "{out:?}");
+    assert!(String::from_utf8(out.stderr)?.contains("the rest stays buried"));
+    project.child("README.md").assert("readme");
+    project.child("src").assert(predicate::path::missing());
+
+    let rec: Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(rec["action"], "extracted");
+    assert_eq!(rec["inner_path"], "README.md");
+    assert_eq!(rec["path"], project.child("README.md").path().to_str().unwrap());
+
+    // L'entrée demeure, allégée de ce qui est sorti
+    let items = util::listed();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["original_path"], project.path().to_str().unwrap());
+    assert_eq!(items[0]["size"].as_u64().unwrap(), size_before - "readme".len() as u64);
+    let trashed = Path::new(items[0]["trashed_path"].as_str().unwrap());
+    assert!(trashed.join("src/lib.rs").exists() && !trashed.join("README.md").exists());
+
+    // Par identifiant, et un sous-dossier entier
+    let id = items[0]["id"].as_str().unwrap();
+    Command::cargo_bin("nrip")?
+        .args(["-r", &format!("{id}/src"), "-y"])
+        .assert()
+        .success();
+    project.child("src/main.rs").assert("fn main() {}");
+    project.child("src/lib.rs").assert("// lib");
+
+    // Le dossier vidé reste enterré; il se restaure normalement
+    assert_eq!(util::listed().len(), 1);
+    Command::cargo_bin("nrip")?
+        .args(["-r", "project", "-y", "--on-conflict", "rename"])
+        .assert()
+        .success();
+    tmp.child("project (restored 2)").assert(predicate::path::is_dir());
+    assert!(util::listed().is_empty());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn conflict_policies_and_to_apply_to_extraction() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let project = tmp.child("project");
+    project.child("a.txt").write_str("old a")?;
+    project.child("b.txt").write_str("b")?;
+    util::bury(project.path());
+    project.child("a.txt").write_str("new a")?;
+
+    Command::cargo_bin("nrip")?
+        .arg("-r")
+        .arg(project.child("a.txt").path())
+        .arg("-y")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Target already exists"));
+    Command::cargo_bin("nrip")?
+        .arg("-r")
+        .arg(project.child("a.txt").path())
+        .args(["-y", "--on-conflict", "rename"])
+        .assert()
+        .success();
+    project.child("a.txt").assert("new a");
+    project.child("a (restored 2).txt").assert("old a");
+
+    let elsewhere = tmp.child("inspect/b-copy.txt");
+    Command::cargo_bin("nrip")?
+        .arg("-r")
+        .arg(project.child("b.txt").path())
+        .args(["-y", "--to"])
+        .arg(elsewhere.path())
+        .assert()
+        .success();
+    elsewhere.assert("b");
+    project.child("b.txt").assert(predicate::path::missing());
+
+    // Absent du dossier enterré: recherche habituelle, sans résultat
+    Command::cargo_bin("nrip")?
+        .arg("-r")
+        .arg(project.child("missing.txt").path())
+        .arg("-y")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No graveyard entry matches"));
+    assert_eq!(util::listed().len(), 1);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn recover_remeasures_an_interrupted_extraction() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let project = tmp.child("project");
+    project.child("big.bin").write_binary(&[0u8; 4096])?;
+    project.child("small.txt").write_str("s")?;
+    util::bury(project.path());
+    let items = util::listed();
+    let trashed = items[0]["trashed_path"].as_str().unwrap().to_owned();
+    let size_before = items[0]["size"].as_u64().unwrap();
+
+    // Déplacé, journalisé, mais l'index n'a pas été réécrit
+    let inner = Path::new(&trashed).join("big.bin");
+    project.create_dir_all()?;
+    std::fs::rename(&inner, project.child("big.bin").path())?;
+    let journal = tmp.child(".xdg/data/nrip/graveyard/.journal");
+    let mut text = std::fs::read_to_string(journal.path())?;
+    text.push_str(&format!(
+        "RESTORE_PENDING\t{}\t{}\n",
+        inner.display(),
+        project.child("big.bin").path().display()
+    ));
+    std::fs::write(journal.path(), text)?;
+
+    Command::cargo_bin("nrip")?
+        .arg("--recover")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("was already restored"));
+    let items = util::listed();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["size"].as_u64().unwrap(), size_before - 4096);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn extract_to_another_filesystem() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let shm = Path::new("/dev/shm");
+    if !shm.is_dir() || shm.metadata()?.dev() == tmp.path().metadata()?.dev() {
+        eprintln!("skipped: no second filesystem");
+        return Ok(());
+    }
+    util::isolate(&tmp);
+    let elsewhere = tempfile::tempdir_in(shm)?;
+    tmp.child("tree/a/b.txt").write_str("b")?;
+    tmp.child("tree/c.txt").write_str("c")?;
+    util::bury(tmp.child("tree").path());
+    let size_before = util::listed()[0]["size"].as_u64().unwrap();
+
+    let dest = elsewhere.path().join("a");
+    Command::cargo_bin("nrip")?
+        .arg("-r")
+        .arg(tmp.child("tree/a").path())
+        .args(["-y", "--to"])
+        .arg(&dest)
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(dest.join("b.txt"))?, "b");
+    assert!(!elsewhere.path().join("a.copying").exists());
+    assert!(util::listed()[0]["size"].as_u64().unwrap() < size_before);
+    tmp.child("tree").assert(predicate::path::missing());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn inner_path_cannot_leave_the_buried_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    tmp.child("project/a.txt").write_str("a")?;
+    util::bury(tmp.child("project").path());
+    let outside = tmp.child("outside.txt");
+    outside.write_str("never buried")?;
+    let id = util::listed()[0]["id"].as_str().unwrap().to_owned();
+    let size_before = util::listed()[0]["size"].as_u64().unwrap();
+
+    let escapes = [
+        format!("{id}/{}", outside.path().display()),
+        format!("{id}/../../../outside.txt"),
+        format!("{}/../../outside.txt", tmp.child("project/a.txt").path().display()),
+    ];
+    for q in &escapes {
+        Command::cargo_bin("nrip")?
+            .args(["-r", q, "-y", "--to"])
+            .arg(tmp.child("stolen").path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Restored 1 item").not());
+    }
+    outside.assert("never buried");
+    tmp.child("stolen").assert(predicate::path::missing());
+    assert_eq!(util::listed()[0]["size"].as_u64().unwrap(), size_before);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn picking_a_directory_and_its_file_restores_once() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    tmp.child("project/sub/f.txt").write_str("f")?;
+    tmp.child("project/other.txt").write_str("o")?;
+    util::bury(tmp.child("project").path());
+
+    // fzf factice: Ctrl-O sur la première ligne, puis `sub/` et `sub/f.txt`
+    let bin = tmp.child("bin/fzf");
+    bin.write_str(
+        "#!/bin/sh\n\
+         case \"$*\" in\n\
+         *--expect*) IFS= read -r first; cat >/dev/null; printf 'ctrl-o\\0%s\\0' \"$first\" ;;\n\
+         *) cat >/dev/null; printf 'sub/\\0sub/f.txt\\0' ;;\n\
+         esac\n",
+    )?;
+    std::fs::set_permissions(bin.path(), std::fs::Permissions::from_mode(0o755))?;
+    let path = format!(
+        "{}:{}",
+        tmp.child("bin").path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    Command::cargo_bin("nrip")?
+        .env("PATH", path)
+        .args(["-r", "-y"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 item(s)."));
+    tmp.child("project/sub/f.txt").assert("f");
+    tmp.child("project/other.txt").assert(predicate::path::missing());
+    assert_eq!(util::listed().len(), 1);
+    Ok(())
+}
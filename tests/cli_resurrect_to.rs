@@ -0,0 +1,148 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::process::Command;
+
+mod util;
+
+#[test]
+#[serial]
+fn single_item_goes_to_the_given_path_or_into_a_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let f = tmp.child("notes.txt");
+    f.write_str("old version")?;
+    util::bury(f.path());
+    f.write_str("current version")?;
+
+    // L'original existe: restauration à côté, sous un autre nom
+    let side = tmp.child("notes.old.txt");
+    Command::cargo_bin("nrip")?
+        .args(["-r", "notes", "-y", "--to"])
+        .arg(side.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes.txt -> ").and(predicate::str::contains("Restored 1 item(s).")));
+    side.assert("old version");
+    f.assert("current version");
+
+    // Dossier existant: on y dépose l'élément sous son nom
+    util::bury(side.path());
+    let inspect = tmp.child("inspect");
+    inspect.create_dir_all()?;
+    Command::cargo_bin("nrip")?
+        .args(["-r", "notes.old", "-y", "--to"])
+        .arg(inspect.path())
+        .assert()
+        .success();
+    inspect.child("notes.old.txt").assert("old version");
+
+    // Jamais d'écrasement
+    util::bury(inspect.child("notes.old.txt").path());
+    Command::cargo_bin("nrip")?
+        .args(["-r", "notes.old", "-y", "--to"])
+        .arg(f.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Target already exists"));
+    f.assert("current version");
+
+    // Pas vers le graveyard
+    Command::cargo_bin("nrip")?
+        .args(["-r", "notes.old", "-y", "--to"])
+        .arg(tmp.child(".xdg/data/nrip/graveyard/x").path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot restore into the graveyard"));
+
+    // Ni vers une corbeille ou un graveyard de montage
+    for held in [".xdg/data/Trash/files/x", "mnt/.nrip-graveyard-1000/x"] {
+        Command::cargo_bin("nrip")?
+            .args(["-r", "notes.old", "-y", "--to"])
+            .arg(tmp.child(held).path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot restore into the graveyard"));
+    }
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn several_items_keep_their_relative_hierarchy() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let a = tmp.child("proj/src/lib.rs");
+    let b = tmp.child("proj/docs/guide.md");
+    a.write_str("lib")?;
+    b.write_str("guide")?;
+    util::bury(a.path());
+    util::bury(b.path());
+    // Le parent d'origine a été déplacé entre-temps
+    std::fs::rename(tmp.child("proj").path(), tmp.child("proj2").path())?;
+
+    let dest = tmp.child("restored");
+    let out = Command::cargo_bin("nrip")?
+        .args(["-r", "", "-y", "--json", "--to"])
+        .arg(dest.path())
+        .output()?;
+    assert!(out.status.success(), "{out:?}");
+    dest.child("src/lib.rs").assert("lib");
+    dest.child("docs/guide.md").assert("guide");
+    tmp.child("proj").assert(predicate::path::missing());
+
+    let doc: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    let mut paths: Vec<&str> = doc["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["path"].as_str().unwrap())
+        .collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [
+            dest.child("docs/guide.md").path().to_str().unwrap(),
+            dest.child("src/lib.rs").path().to_str().unwrap(),
+        ]
+    );
+
+    // Journal: destination réelle, pas l'origine
+    let journal = std::fs::read_to_string(tmp.child(".xdg/data/nrip/graveyard/.journal").path())?;
+    assert!(journal.lines().any(|l| l.starts_with("RESTORE_DONE")
+        && l.ends_with(dest.child("src/lib.rs").path().to_str().unwrap())));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn restore_to_another_filesystem() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    let shm = Path::new("/dev/shm");
+    if !shm.is_dir() || shm.metadata()?.dev() == tmp.path().metadata()?.dev() {
+        eprintln!("skipped: no second filesystem");
+        return Ok(());
+    }
+    util::isolate(&tmp);
+    let elsewhere = tempfile::tempdir_in(shm)?;
+    tmp.child("tree/a/b.txt").write_str("b")?;
+    util::bury(tmp.child("tree").path());
+
+    let dest = elsewhere.path().join("copy-of-tree");
+    Command::cargo_bin("nrip")?
+        .args(["-r", "tree", "-y", "--to"])
+        .arg(&dest)
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(dest.join("a/b.txt"))?, "b");
+    assert!(!elsewhere.path().join("copy-of-tree.copying").exists());
+    Command::cargo_bin("nrip")?
+        .args(["--json", "-l"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"items\": []"));
+    Ok(())
+}
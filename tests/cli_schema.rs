@@ -0,0 +1,133 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+#[test]
+#[serial]
+fn unversioned_index_is_migrated_with_a_backup() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "color = \"never\"\n");
+    let data = tmp.child(".xdg/data/nrip");
+    let gy = data.child("graveyard");
+
+    // Index v0: pas de `version`, ni de `kind`
+    let buried = gy.child("20240101T000000__abcdefgh__notes");
+    buried.create_dir_all()?;
+    let v0 = format!(
+        r#"{{"items":[{{"original_path":"{}","trashed_path":"{}","deleted_at":1704067200}}]}}"#,
+        tmp.child("notes").path().display(),
+        buried.path().display()
+    );
+    data.child("index.json").write_str(&v0)?;
+
+    // Lecture: migration en mémoire, fichier intact
+    Command::cargo_bin("nrip")?
+        .arg("-l")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes"));
+    data.child("index.json").assert(v0.as_str());
+
+    // Première écriture: sauvegarde puis réécriture en v1
+    let f = tmp.child("new.txt");
+    f.write_str("n")?;
+    Command::cargo_bin("nrip")?
+        .arg(f.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("upgraded").and(predicate::str::contains("index.json.v0.bak")));
+    data.child("index.json.v0.bak").assert(v0.as_str());
+
+    let v: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(data.child("index.json").path())?)?;
+    assert_eq!(v["version"], 1);
+    let notes = v["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["original_path"].as_str().unwrap().ends_with("notes"))
+        .unwrap();
+    // `kind` relu sur le fichier enterré
+    assert_eq!(notes["kind"], "Dir");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn newer_index_is_refused_and_left_untouched() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "color = \"never\"\n");
+    let index = tmp.child(".xdg/data/nrip/index.json");
+    let future = r#"{"version":99,"items":[],"tags":["from the future"]}"#;
+    index.write_str(future)?;
+
+    let f = tmp.child("f.txt");
+    f.write_str("f")?;
+    Command::cargo_bin("nrip")?
+        .arg(f.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("schema v99").and(predicate::str::contains("upgrade nrip")));
+    f.assert("f");
+    index.assert(future);
+
+    Command::cargo_bin("nrip")?
+        .arg("-l")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("schema v99"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn newer_log_index_is_refused_before_reading_operations() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "[index]\nbackend = \"log\"\n");
+    let log = tmp.child(".xdg/data/nrip/index.log");
+    let future = "{\"version\":2}\n{\"op\":\"tag\",\"name\":\"x\"}\n";
+    log.write_str(future)?;
+
+    Command::cargo_bin("nrip")?
+        .arg("-l")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("schema v2"));
+    log.assert(future);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn unknown_entry_fields_survive_a_rewrite() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate_with_config(&tmp, "color = \"never\"\n");
+    let data = tmp.child(".xdg/data/nrip");
+    let buried = data.child("graveyard/20240101T000000__abcdefgh__notes");
+    buried.create_dir_all()?;
+    let v1 = format!(
+        r#"{{"version":1,"items":[{{"original_path":"{}","trashed_path":"{}","deleted_at":1704067200,"kind":"Dir","tags":["keep"]}}]}}"#,
+        tmp.child("notes").path().display(),
+        buried.path().display()
+    );
+    data.child("index.json").write_str(&v1)?;
+
+    let f = tmp.child("new.txt");
+    f.write_str("n")?;
+    Command::cargo_bin("nrip")?.arg(f.path()).assert().success();
+
+    let v: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(data.child("index.json").path())?)?;
+    let notes = v["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["original_path"].as_str().unwrap().ends_with("notes"))
+        .unwrap();
+    assert_eq!(notes["tags"], serde_json::json!(["keep"]));
+    Ok(())
+}
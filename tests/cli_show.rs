@@ -0,0 +1,103 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serde_json::Value;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+#[test]
+#[serial]
+fn show_lists_the_buried_tree_without_restoring() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let project = tmp.child("project");
+    project.child("src/main.rs").write_str("fn main() {}")?;
+    project.child("README.md").write_str("readme")?;
+    util::bury(project.path());
+
+    Command::cargo_bin("nrip")?
+        .args(["--show", "project"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("├── README.md")
+                .and(predicate::str::contains("└── src/"))
+                .and(predicate::str::contains("    └── main.rs"))
+                .and(predicate::str::contains("12 B"))
+                .and(predicate::str::contains("2 file(s), 1 dir(s)")),
+        );
+    project.assert(predicate::path::missing());
+
+    // Un chemin intérieur, comme pour -r
+    let out = Command::cargo_bin("nrip")?
+        .args(["--json", "--show"])
+        .arg(project.child("src").path())
+        .output()?;
+    assert!(out.status.success(), "{out:?}");
+    let doc: Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(doc["command"], "show");
+    let items = doc["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["kind"], "dir");
+    assert_eq!(items[1]["path"], project.child("src/main.rs").path().to_str().unwrap());
+    assert_eq!(items[1]["size"], 12);
+    let trashed = items[1]["trashed_path"].as_str().unwrap();
+    assert_eq!(std::fs::read_to_string(trashed)?, "fn main() {}");
+    assert!(items[1]["mtime"].as_i64().unwrap() > 0);
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn cat_prints_a_buried_file() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let notes = tmp.child("notes.txt");
+    notes.write_str("line 1\nline 2\n")?;
+    util::bury(notes.path());
+    let project = tmp.child("project");
+    project.child("src/main.rs").write_str("fn main() {}")?;
+    util::bury(project.path());
+
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes"])
+        .assert()
+        .success()
+        .stdout("line 1\nline 2\n");
+    Command::cargo_bin("nrip")?
+        .arg("--cat")
+        .arg(project.child("src/main.rs").path())
+        .assert()
+        .success()
+        .stdout("fn main() {}");
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "project"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is a directory; use --show"));
+
+    // Même résolution que -r, mais une seule entrée acceptée
+    tmp.child("notes.md").write_str("other notes")?;
+    util::bury(tmp.child("notes.md").path());
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("matches 2 entries; pick one by ID"));
+    // Deux versions d'un même chemin: la dernière
+    notes.write_str("again")?;
+    util::bury(notes.path());
+    Command::cargo_bin("nrip")?
+        .args(["--cat", "notes.txt"])
+        .assert()
+        .success()
+        .stdout("again");
+    Command::cargo_bin("nrip")?
+        .args(["--show", "nothing-like-this"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No graveyard entry matches"));
+    Ok(())
+}
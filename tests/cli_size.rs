@@ -0,0 +1,116 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::process::Command;
+
+mod util;
+
+fn recorded_size(tmp: &assert_fs::TempDir, name: &str) -> (u64, u64) {
+    let index = tmp.child(".xdg/data/nrip/index.json");
+    let v: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(index.path()).unwrap()).unwrap();
+    let e = v["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["original_path"].as_str().unwrap().ends_with(name))
+        .unwrap();
+    (
+        e["size"].as_u64().unwrap(),
+        e["disk_usage"].as_u64().unwrap(),
+    )
+}
+
+#[test]
+#[serial]
+fn bury_records_recursive_directory_size() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    let dir = tmp.child("tree");
+    dir.child("a/one.bin").write_binary(&[0u8; 3000])?;
+    dir.child("a/b/two.bin").write_binary(&[1u8; 5000])?;
+    Command::cargo_bin("nrip")?.arg(dir.path()).assert().success();
+
+    let (apparent, disk) = recorded_size(&tmp, "tree");
+    // contenu + entrées de répertoire
+    assert!(apparent >= 8000, "apparent = {apparent}");
+    assert!(disk >= 8000, "disk = {disk}");
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+#[serial]
+fn hardlinks_are_counted_once() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    let dir = tmp.child("linked");
+    dir.child("data.bin").write_binary(&[7u8; 50_000])?;
+    std::fs::hard_link(dir.child("data.bin").path(), dir.child("alias.bin").path())?;
+    Command::cargo_bin("nrip")?.arg(dir.path()).assert().success();
+
+    let (apparent, _) = recorded_size(&tmp, "linked");
+    assert!(apparent < 100_000, "apparent = {apparent}");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn list_and_cremate_show_human_sizes() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+
+    let file = tmp.child("payload.bin");
+    file.write_binary(&vec![0u8; 3 * 1024 * 1024])?;
+    Command::cargo_bin("nrip")?.arg(file.path()).assert().success();
+
+    Command::cargo_bin("nrip")?
+        .args(["--list", "--color", "never"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3.0 MiB"));
+
+    Command::cargo_bin("nrip")?
+        .args(["-c", "payload", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3.0 MiB"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn list_does_not_measure_entries_without_a_recorded_size() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = assert_fs::TempDir::new()?;
+    util::isolate(&tmp);
+    let data = tmp.child(".xdg/data/nrip");
+    let buried = data.child("graveyard/20240101T000000__abcdefgh__old.bin");
+    buried.write_binary(&vec![0u8; 3 * 1024 * 1024])?;
+    data.child("index.json").write_str(&format!(
+        r#"{{"version":1,"items":[{{"original_path":"{}","trashed_path":"{}","deleted_at":1704067200,"kind":"File"}}]}}"#,
+        tmp.child("old.bin").path().display(),
+        buried.path().display()
+    ))?;
+
+    Command::cargo_bin("nrip")?
+        .args(["--list", "--color", "never", "--format", "{basename} [{size}] [{disk_usage}]"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old.bin [-] [-]"));
+    Command::cargo_bin("nrip")?
+        .args(["--list", "--tree", "--color", "never"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old.bin  -").and(predicate::str::contains("MiB").not()));
+
+    // Les résumés mesurent toujours sur place
+    Command::cargo_bin("nrip")?
+        .args(["-c", "old.bin", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3.0 MiB"));
+    Ok(())
+}
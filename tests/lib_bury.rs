@@ -48,10 +48,10 @@ fn bury_twice_same_basename_produces_distinct_targets() -> Result<(), Box<dyn st
 
     let mut trashed = vec![];
     for e in entries {
-        if let Some(p) = e.get("trashed_path").and_then(|s| s.as_str()) {
-            if p.ends_with("dup.txt") {
-                trashed.push(p.to_string());
-            }
+        if let Some(p) = e.get("trashed_path").and_then(|s| s.as_str())
+            && p.ends_with("dup.txt")
+        {
+            trashed.push(p.to_string());
         }
     }
     assert!(
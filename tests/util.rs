@@ -1,4 +1,15 @@
+//! Outils partagés par les tests d'intégration (`mod util;`)
+
+// Chaque fichier de test n'en utilise qu'une partie
+#![allow(dead_code)]
+
+use assert_cmd::prelude::*;
+use assert_fs::TempDir;
+use assert_fs::prelude::*;
+use serde_json::Value;
 use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Command;
 
 pub fn set_var<K: AsRef<OsStr>, V: AsRef<OsStr>>(k: K, v: V) {
     unsafe {
@@ -6,9 +17,44 @@ pub fn set_var<K: AsRef<OsStr>, V: AsRef<OsStr>>(k: K, v: V) {
     }
 }
 
-#[allow(dead_code)]
 pub fn remove_var<K: AsRef<OsStr>>(k: K) {
     unsafe {
         std::env::remove_var(k);
     }
 }
+
+/// HOME et XDG_* sous `tmp`: config, index et graveyard propres au test
+pub fn isolate(tmp: &TempDir) {
+    set_var("HOME", tmp.path());
+    set_var("XDG_DATA_HOME", tmp.child(".xdg/data").path());
+    set_var("XDG_CONFIG_HOME", tmp.child(".xdg/config").path());
+}
+
+/// `isolate`, avec `config` pour config.toml
+pub fn isolate_with_config(tmp: &TempDir, config: &str) {
+    isolate(tmp);
+    tmp.child(".xdg/config/nrip/config.toml")
+        .write_str(config)
+        .unwrap();
+}
+
+/// `nrip <p>`, qui doit réussir
+pub fn bury(p: &Path) {
+    Command::cargo_bin("nrip").unwrap().arg(p).assert().success();
+}
+
+/// Entrées publiées par `nrip --json -l`
+pub fn listed() -> Vec<Value> {
+    let out = Command::cargo_bin("nrip")
+        .unwrap()
+        .args(["--json", "-l"])
+        .output()
+        .unwrap();
+    let doc: Value = serde_json::from_slice(&out.stdout).unwrap();
+    doc["items"].as_array().unwrap().clone()
+}
+
+/// `index.json` tel quel (vide s'il n'existe pas encore)
+pub fn index_str(tmp: &TempDir) -> String {
+    std::fs::read_to_string(tmp.child(".xdg/data/nrip/index.json").path()).unwrap_or_default()
+}